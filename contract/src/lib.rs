@@ -1,12 +1,394 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::{env, near_bindgen}; // env is used for logging
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::{Base64VecU8, U128, U64}; // u128 doesn't fit in a JSON number, so balances are returned as strings
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{
+    env, near_bindgen, require, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault, Promise, PromiseOrValue, PromiseResult,
+}; // env is used for logging
+use std::collections::BTreeMap;
+
+// Conservative gas figures (in gas units, 1 TGas = 10^12) used to advise frontends how much gas to attach.
+const TGAS: u64 = 1_000_000_000_000;
+const GAS_FOR_GUESS: u64 = 10 * TGAS; // hashing, comparing and logging
+const GAS_FOR_REWARD: u64 = 5 * TGAS; // a NEAR transfer to the winner
+const GAS_FOR_MINT: u64 = 20 * TGAS; // writing a prize NFT into storage
+const GAS_FOR_CROSS_CONTRACT: u64 = 30 * TGAS; // a call out to another contract plus its callback
+const GAS_FOR_NFT_ON_MINT: u64 = 10 * TGAS; // handed to the winner's account to react to a new NFT
+const GAS_FOR_RESOLVE_MINT: u64 = 5 * TGAS; // our own callback logging how the notification went
+const GAS_FOR_NFT_CHECK: u64 = 10 * TGAS; // `nft_tokens_for_owner` view on an NFT contract
+const GAS_FOR_RESOLVE_GATED_GUESS: u64 = 30 * TGAS; // finishing the guess once the NFT check is back
+const GAS_FOR_FT_TRANSFER: u64 = 10 * TGAS; // `ft_transfer` of a reward on the token contract
+// Least prepaid gas for a guess which may pay a token reward, so the transfer isn't starved of gas.
+// Enough for one recipient, a team needs GAS_FOR_FT_TRANSFER more per extra member.
+const MIN_GAS_FOR_REWARD: u64 = GAS_FOR_GUESS + GAS_FOR_FT_TRANSFER + 10 * TGAS;
+
+const MAX_CALLBACK_URL_LEN: usize = 256;
+const MAX_BANNER_LEN: usize = 280;
+const MAX_NOTE_LEN: usize = 1024; // room for a note encrypted client-side
+const MAX_THEME_NAME_LEN: usize = 32;
+
+// Time left before a deadline at which a "countdown" event is emitted: 1 hour and 10 minutes.
+const COUNTDOWN_MILESTONES_NS: [u64; 2] = [60 * 60 * 1_000_000_000, 10 * 60 * 1_000_000_000];
+
+// How long a winner has to claim a prize NFT before the owner may take it back (30 days).
+const NFT_RECLAIM_DELAY_NS: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+// How long the host has to open a requested letter before the player may take its fee back (1 day).
+const LETTER_REFUND_DELAY_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// Burned guess fees are sent here: an implicit account whose key is all zeros, so nobody can spend from it.
+const BURN_ACCOUNT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// Length of a UTC day, block timestamps divided by this give the day number.
+const DAY_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// NEPs the contract fully implements, returned by `supported_standards`. Keep in sync with the methods:
+// the prize NFTs only have part of NEP-171 (`nft_transfer`, `nft_approve`) and `ft_on_transfer` only
+// receives NEP-141 tokens, so neither standard is listed.
+const SUPPORTED_STANDARDS: [&str; 1] = [
+    "nep297", // events, see `log_event`
+];
+
+// Storage a new puzzle takes besides its meta (the puzzle record, catalog entries, solution hash lookup and
+// author stats, measured with a short id) and what each clue with a sha256 answer hash and no hint adds, for
+// `estimate_puzzle_storage`.
+const PUZZLE_BASE_BYTES: u64 = 810;
+const CLUE_BYTES: u64 = 86;
+
+// Upper bound on how many accounts can be looked up in one view call, to keep gas bounded.
+const MAX_BATCH_QUERY: usize = 100;
+
+// Largest team, a win pays every member with its own transfer so this bounds the gas of a winning guess.
+const MAX_TEAM_SIZE: usize = 5;
 
 // ------------------------------------------ CONTRACT STATE --------------------------------------------------
+// Every persistent collection needs its own unique storage prefix.
+#[derive(BorshStorageKey, BorshSerialize)]
+enum StorageKey {
+    Puzzles,
+    Solvers,
+    InviteCodes,
+    Attempts,
+    Tokens,
+    Teams,
+    TeamMembers,
+    SolutionHashes,
+    Vesting,
+    SolveFeed { puzzle_id_hash: Vec<u8> },
+    Participants { puzzle_id_hash: Vec<u8> },
+    PuzzleTokens { puzzle_id_hash: Vec<u8> },
+    PuzzleSolves,
+    SolvedClues,
+    ClueAttempts,
+    Blacklist,
+    Earnings,
+    Allowlists,
+    LastGuesses,
+    AuthorStats,
+    AssistedPlayers,
+    LettersRevealed,
+    ClueSolves,
+    Eliminated,
+    Notes,
+    RevealedPositions,
+    TeamInvites,
+    LetterRequests,
+    RemovedPuzzles,
+}
+
+pub type TokenId = String;
+
+// A `request_letter` waiting on the host: (account, escrowed fee, requested at).
+type LetterRequest = (AccountId, Balance, u64);
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Clue {
+    answer_hash: String, // hex encoded sha256 of the clue's answer
+    length_hint: Option<u8>, // number of characters in the answer, only if the host chose to reveal it
+    weight: u8, // how much the clue counts towards the score, harder clues can weigh more
+    hint: Option<String>, // shown to players who keep failing this clue
+    prerequisites: Vec<u16>, // clues the account must have solved before it can answer this one
+    bonus: Option<Balance>, // set = optional clue: not needed to complete the puzzle, adds this much to the payout
+    letter_commitments: Vec<String>, // per position: hex sha256 of the letter followed by the host's secret salt
+    opened_letters: BTreeMap<u32, String>, // position -> letter (a single character), once the host opened its commitment
+}
+
+// Descriptive, public information about a puzzle, passed in when adding it and returned to frontends.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PuzzleMeta {
+    pub title: String,
+    pub callback_url: Option<String>, // informational: where a frontend/indexer should POST when the puzzle is solved
+    pub theme: Option<String>, // for branded rendering: a hex color ("#1e90ff" or "#19f") or a theme name ("dark-mode")
+}
+
+// How lenient matching is: normalization applied to an answer before hashing, so e.g. "Near " can match "near".
+// The stored hash must have been computed from an answer normalized the same way.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NormalizeRules {
+    pub trim: bool, // drop surrounding whitespace
+    pub collapse_whitespace: bool, // runs of whitespace become a single space
+    pub lowercase: bool,
+    pub strip_punctuation: bool, // drop anything that isn't a letter, digit or whitespace, "don't" -> "dont"
+    pub unicode_nfc: bool, // Unicode NFC, only works when built with the `unicode-normalization` feature
+}
+
+// Hash function used for solutions and guesses. Sha256 and Keccak256 are provided by the NEAR runtime.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HashAlgo {
+    Sha256,
+    Keccak256,
+    Blake2b, // BLAKE2b-512, only works when the contract is built with the `blake2` feature
+}
+
+// How a puzzle's prize is paid out.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RewardMode {
+    FirstOnly, // each new solver is paid right away from what's left, so the first one takes the pool
+    SplitAmongWindow, // everyone solving within the reward window after the first solve shares the pool equally
+    None, // bragging rights only: guesses are free and solves are recorded, but nobody is paid or minted a prize
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PuzzleStatus {
+    NotStarted, // not open for guessing yet
+    Active,
+    Solved, // at least one account has solved it
+    Expired, // past its deadline without being solved
+    Cancelled, // withdrawn by the owner, guess fees were refunded
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Puzzle {
+    solution_hash: String, // hex encoded sha256 of the full solution
+    meta: PuzzleMeta,
+    frozen: bool, // once frozen the puzzle can no longer be edited
+    clues: BTreeMap<u16, Clue>, // clue number -> clue
+    deadline: Option<u64>, // block timestamp (ns) after which the puzzle expires, None = never
+    start_timestamp: Option<u64>, // block timestamp (ns) before which guesses are refused, None = open right away
+    solve_feed: Vector<(AccountId, u64)>, // (solver, block timestamp) in solve order
+    participants: UnorderedMap<AccountId, Balance>, // everyone who guessed -> guess fees they paid
+    tokens: Vector<TokenId>, // prize NFTs minted for this puzzle, in minting order
+    cancelled: bool,
+    required_nft: Option<AccountId>, // if set, only holders of a token from this NFT contract may guess
+    last_status: PuzzleStatus, // last status an event was emitted for, so each transition is logged once
+    reveal_at: u64, // block timestamp (ns) from which `sealed_plaintext` is shown
+    sealed_plaintext: Option<String>, // the solution, checked against `solution_hash` when it was sealed
+    author: AccountId, // receives tips, the account which added the puzzle unless the owner changes it
+    tips: Balance, // total tipped to the author so far
+    guess_lengths: BTreeMap<usize, u32>, // guess length in characters -> number of guesses, the guesses aren't kept
+    prize_pool: Balance, // the puzzle's own prize, paid instead of the shared pool while it isn't empty
+    soulbound_prizes: bool, // prize NFTs minted for this puzzle are soulbound
+    normalize_rules: NormalizeRules, // how lenient matching answers to this puzzle is
+    last_countdown: Option<u64>, // last countdown milestone (time left, ns) an event was emitted for
+    linked_puzzle: Option<String>, // its pool is added to this puzzle's payout (grand finale)
+    paused: bool, // when paused nobody can guess on this puzzle, the global pause still applies on top
+    reward_token: Option<AccountId>, // NEP-141 token the puzzle's `prize_pool` is held in, None = NEAR
+    allowlist_only: bool, // when set, only accounts on the puzzle's allowlist can guess
+    access_password_hash: Option<String>, // hex sha256 of the password every guess must come with, None = no password
+    reward_mode: RewardMode,
+    reward_window_ns: u64, // with SplitAmongWindow: how long after the first solve others can still share the pool
+    window_solvers: Vec<AccountId>, // with SplitAmongWindow: accounts credited within the window, paid by `finalize_rewards`
+    rewards_finalized: bool, // set once `finalize_rewards` paid the window solvers
+    max_attempts: Option<u64>, // guesses (by everyone together) after which the puzzle closes, None = no cap
+    guess_count: u64, // guesses made on the puzzle so far
+    practice_mode: bool, // when set, correct guesses are confirmed but not recorded, paid or rewarded with an NFT
+    max_winners: Option<u32>, // how many solvers can be paid a reward, None = all of them (while the pool lasts)
+    rewarded_winners: u32, // solvers paid a reward so far
+    sudden_death: bool, // when set, an account's first wrong guess bars it from the puzzle for good
+    grid_dimensions: Option<(u16, u16)>, // (rows, columns) the host announced for the grid
+    grid: Vec<Vec<bool>>, // rows of cells, true = fillable, checked against `grid_dimensions` by `validate_grid`
+}
+
+// A prize NFT minted for a solver.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Token {
+    owner_id: AccountId,
+    minted_at: u64, // block timestamp (ns) of the mint
+    claimed: bool, // set once the winner claims it, after that it can't be reclaimed
+    soulbound: bool, // an achievement which can't be transferred or approved for transfer
+    approved_account_ids: Vec<AccountId>, // accounts the owner allowed to transfer the token
+}
+
+// Per account bookkeeping of guesses.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Attempts {
+    count: u64, // number of guesses made
+    first_guess_at: u64, // block timestamp (ns) of the first guess
+    last_guess_at: u64, // block timestamp (ns) of the latest guess
+    streak: u32, // consecutive UTC days with a guess, up to the day of `last_guess_at`
+}
+
+// A player's progress as moved by `export_progress`/`import_progress` (Borsh serialized).
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PlayerProgress {
+    attempts: Option<Attempts>,
+    solved_clues: Vec<(String, u16)>, // (puzzle id, clue number)
+    clue_failures: Vec<(String, u16, u32)>, // (puzzle id, clue number, wrong answers)
+}
+
+// A reward which is released gradually instead of paid out at once.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VestingEntitlement {
+    total: Balance, // full amount being vested
+    claimed: Balance, // amount already paid out
+    start: u64, // block timestamp (ns) when vesting started
+    duration: u64, // ns until everything is claimable
+}
+
+// Returned by `balance_breakdown`, all amounts in yoctoNEAR.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalanceBreakdown {
+    pub total: U128,
+    pub prize_pool: U128,
+    pub collected_fees: U128,
+    pub letter_escrow: U128,
+    pub storage_stake: U128,
+    pub free: U128,
+}
+
+// Settings the contract started with, recorded by `new` for auditing a deployment.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InitParams {
+    pub owner_id: AccountId,
+    pub solution_hash: String, // the `solution` passed to `new`
+    pub hash_algo: HashAlgo,
+    pub salted: bool, // whether answers are salted before hashing, they never are so far
+    pub guess_fee: U128,
+    pub min_stake_to_play: U128,
+}
+
+// Returned by `health`, for monitoring.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HealthReport {
+    pub initialized: bool,
+    pub paused: bool,
+    pub puzzle_count: u64,
+    pub balance_ok: bool, // the balance covers storage plus the shared prize pool and collected fees
+}
+
+// Returned by `author_stats`: an author's reputation over all their puzzles.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuthorStats {
+    pub puzzles_created: u64, // puzzles they are currently the author of
+    pub total_solvers: u64, // solves of their puzzles (while they were the author)
+    pub total_tips: U128, // yoctoNEAR tipped to them
+}
+
+// What the host signs off-chain for `guess_with_attestation`: "this account solved this puzzle".
+// `puzzle_id` None stands for the crossword set in `new`.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Attestation {
+    account_id: AccountId,
+    puzzle_id: Option<String>,
+}
+
+// Returned by `reward_info`: what the next solver could win and in which currency.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardInfo {
+    pub token: Option<AccountId>, // NEP-141 token contract, None = NEAR
+    pub amount: U128, // in the token's smallest unit (yoctoNEAR for NEAR)
+}
+
+// Returned by `puzzle_view`: everything a puzzle page needs in one call.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PuzzleView {
+    pub meta: PuzzleMeta,
+    pub status: PuzzleStatus,
+    pub potential_reward: U128, // the most the next solver could win (yoctoNEAR), 0 if nothing can be won
+    pub attempts_remaining: Option<u64>, // None = guesses are unlimited
+}
+
+// State layout of the first deployed version, which only stored the solution. Read by `migrate`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldContract {
+    crossword_solution: String,
+}
+
 #[near_bindgen] // macro used on a struct and fn implementations to generate code to be a valid NEAR contract and expose intended fns for external callability.
-#[derive(Default, BorshDeserialize, BorshSerialize)] // Borsh: Binary Object Representation Serializer for Hashing to convert code to 0,1 efficiently.
+#[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)] // Borsh: Binary Object Representation Serializer for Hashing to convert code to 0,1 efficiently. PanicOnDefault forces the use of `new`.
 pub struct Contract {
     // struct is public so other code can use it, but the fields inside are private (no mut)
     crossword_solution: String, // PERSISTENT STORAGE (STAKING REQUIRED)
+    owner_id: AccountId, // account that called `new`, allowed to change settings
+    // Feature flags, switched on/off by the owner
+    rewards_enabled: bool,
+    minting_enabled: bool,
+    cross_contract_enabled: bool,
+    puzzles: UnorderedMap<String, Puzzle>, // puzzle id -> puzzle
+    guess_fee: Balance, // yoctoNEAR a player must attach to each guess
+    min_stake_to_play: Balance, // yoctoNEAR a player must be able to attach to guess (anti-sybil), refunded above the fee
+    collected_fees: Balance, // guess fees received so far
+    prize_pool: Balance, // NEAR set aside for winners
+    solvers: LookupSet<AccountId>, // accounts which have guessed the solution
+    paused: bool, // when paused nobody can guess
+    auto_unpause_at: Option<u64>, // block timestamp (ns) after which a pause lapses by itself
+    require_invite: bool, // when set, every guess must come with an unused invite code
+    invite_codes: UnorderedSet<String>, // hex sha256 hashes of the unused invite codes
+    attempts: LookupMap<AccountId, Attempts>, // account -> its guessing history
+    cooldown_ns: u64, // minimum time between two guesses by the same account
+    tokens: UnorderedMap<TokenId, Token>, // prize NFTs minted so far
+    next_token_id: u64, // counter used to create unique token ids
+    notify_on_mint: bool, // when set, winners' accounts are called with `nft_on_mint` after their prize is minted
+    teams: LookupMap<AccountId, String>, // member -> team name
+    team_members: LookupMap<String, Vec<AccountId>>, // team name -> members, in joining order
+    team_invites: LookupSet<(String, AccountId)>, // (team name, account) for accounts a member invited to join
+    unique_solutions: bool, // when set, two puzzles can't share the same solution hash
+    solution_hashes: LookupMap<String, String>, // reverse index: solution hash -> puzzle id
+    vesting_ns: u64, // 0 = rewards are paid instantly, otherwise they vest linearly over this period
+    vesting: LookupMap<AccountId, VestingEntitlement>, // winner -> reward still vesting
+    puzzle_solves: LookupMap<(String, AccountId), u64>, // (puzzle id, solver) -> index in the puzzle's solve feed
+    bonus_bps: Option<(u32, u32)>, // (min, max) random payout multiplier for a puzzle's first solver, 10_000 = 1x
+    hash_algo: HashAlgo, // how guesses are hashed before comparing them to stored hashes
+    normalize_rules: NormalizeRules, // matching rules for the crossword set in `new`, puzzles have their own
+    attempts_public: bool, // when not set, only the account itself can read its number of attempts
+    solved_clues: LookupSet<(String, AccountId, u16)>, // (puzzle id, account, clue number) for every solved clue
+    humans_only: bool, // when set, guesses must come straight from the signer, not through another contract
+    hint_after_failures: u32, // wrong answers to a clue after which its hint unlocks, 0 = hints are off
+    attempts_per_clue: LookupMap<(String, AccountId, u16), u32>, // (puzzle id, account, clue number) -> wrong answers
+    blacklist: UnorderedSet<AccountId>, // accounts caught cheating, they can't guess or claim prizes
+    attestation_key: Option<Vec<u8>>, // host's ed25519 public key (32 bytes) for signed answer attestations
+    rollover_bps: u32, // part of every payout (10_000 = all of it) moved to `rollover_puzzle`'s pool instead
+    rollover_puzzle: Option<String>, // puzzle receiving the rollover, None = no rollover
+    init_params: InitParams, // never changes after `new` (or `migrate`)
+    max_reward_per_account: Option<Balance>, // cap on what one account can win in total, None = no cap
+    earnings: LookupMap<AccountId, Balance>, // account -> rewards it has won so far (vesting ones included)
+    allowlists: LookupSet<(String, AccountId)>, // (puzzle id, account) for accounts allowed on allowlist-only puzzles
+    solver_salt: Option<Vec<u8>>, // set = anonymous solvers: puzzle solve feeds hold salted hashes, not accounts (readable, not a secret)
+    reject_repeat_guesses: bool, // when set, an account can't submit the same guess twice in a row
+    last_guesses: LookupMap<AccountId, (Option<String>, String)>, // account -> (puzzle id, hash) of its latest guess
+    created_at: u64, // block timestamp (ns) of `new`
+    min_payout: Balance, // rewards below this aren't paid out (no dust transfers), they stay in the pool
+    charity_account: Option<AccountId>, // receives the pools of expired puzzles instead of the owner
+    author_stats: LookupMap<AccountId, AuthorStats>, // author -> aggregated stats of their puzzles
+    burn_fees: bool, // when set, guess fees are burned instead of collected
+    total_gas_logged: u128, // gas used by all `guess_solution` calls so far, sampled at the end of each
+    gas_samples: u64, // number of guesses summed into `total_gas_logged`
+    accessibility_mode: bool, // when set, clues failed `auto_solve_after_failures` times are solved for the player
+    auto_solve_after_failures: u32,
+    assisted_players: LookupSet<(String, AccountId)>, // (puzzle id, account) for players who had a clue auto-solved
+    banner: Option<String>, // notice for frontends to display, e.g. "Maintenance at 5pm"
+    letter_fee: Balance, // fee for an account's first letter on a puzzle, doubling with each further one
+    letters_revealed: LookupMap<(String, AccountId), u32>, // (puzzle id, account) -> letters it has bought
+    clue_solves: LookupMap<(String, u16), u32>, // (puzzle id, clue number) -> accounts that solved the clue
+    eliminated: LookupSet<(String, AccountId)>, // (puzzle id, account) for accounts knocked out by sudden death
+    notes: LookupMap<AccountId, String>, // winner -> note it attached to its solve
+    revealed_positions: LookupMap<(String, AccountId), Vec<(u16, u32)>>, // (puzzle id, account) -> (clue, position) bought
+    letter_requests: LookupMap<(String, u16, u32), Vec<LetterRequest>>, // (puzzle id, clue, position) -> requests waiting on `open_letter`
+    letter_escrow: Balance, // fees held in `letter_requests`, moved to `collected_fees` when the letter is opened
+    removed_puzzles: LookupSet<String>, // ids of removed puzzles, never reused since per-account records outlive them
 }
 
 // ------------------------------------------ CONTRACT METHODS --------------------------------------------------
@@ -15,78 +397,5511 @@ impl Contract { // impl provides methods on structs and enums
     // Immutable function. 
     #[init] // macro
     pub fn new(solution: String) -> Self { // set the solution once, right after deploying contract. 
+        let init_params = InitParams {
+            owner_id: env::predecessor_account_id(),
+            solution_hash: solution.clone(),
+            hash_algo: HashAlgo::Sha256,
+            salted: false,
+            guess_fee: U128(0),
+            min_stake_to_play: U128(0),
+        };
         Self {
             crossword_solution: solution,
+            owner_id: env::predecessor_account_id(),
+            rewards_enabled: false,
+            minting_enabled: false,
+            cross_contract_enabled: false,
+            puzzles: UnorderedMap::new(StorageKey::Puzzles),
+            guess_fee: 0,
+            min_stake_to_play: 0,
+            collected_fees: 0,
+            prize_pool: 0,
+            solvers: LookupSet::new(StorageKey::Solvers),
+            paused: false,
+            auto_unpause_at: None,
+            require_invite: false,
+            invite_codes: UnorderedSet::new(StorageKey::InviteCodes),
+            attempts: LookupMap::new(StorageKey::Attempts),
+            cooldown_ns: 0,
+            tokens: UnorderedMap::new(StorageKey::Tokens),
+            next_token_id: 0,
+            notify_on_mint: false,
+            teams: LookupMap::new(StorageKey::Teams),
+            team_members: LookupMap::new(StorageKey::TeamMembers),
+            team_invites: LookupSet::new(StorageKey::TeamInvites),
+            unique_solutions: false,
+            solution_hashes: LookupMap::new(StorageKey::SolutionHashes),
+            vesting_ns: 0,
+            vesting: LookupMap::new(StorageKey::Vesting),
+            puzzle_solves: LookupMap::new(StorageKey::PuzzleSolves),
+            bonus_bps: None,
+            hash_algo: HashAlgo::Sha256,
+            normalize_rules: NormalizeRules::default(),
+            attempts_public: true,
+            solved_clues: LookupSet::new(StorageKey::SolvedClues),
+            humans_only: false,
+            hint_after_failures: 0,
+            attempts_per_clue: LookupMap::new(StorageKey::ClueAttempts),
+            blacklist: UnorderedSet::new(StorageKey::Blacklist),
+            attestation_key: None,
+            rollover_bps: 0,
+            rollover_puzzle: None,
+            init_params,
+            max_reward_per_account: None,
+            earnings: LookupMap::new(StorageKey::Earnings),
+            allowlists: LookupSet::new(StorageKey::Allowlists),
+            solver_salt: None,
+            reject_repeat_guesses: false,
+            last_guesses: LookupMap::new(StorageKey::LastGuesses),
+            created_at: env::block_timestamp(),
+            min_payout: 0,
+            charity_account: None,
+            author_stats: LookupMap::new(StorageKey::AuthorStats),
+            burn_fees: false,
+            total_gas_logged: 0,
+            gas_samples: 0,
+            accessibility_mode: false,
+            auto_solve_after_failures: 0,
+            assisted_players: LookupSet::new(StorageKey::AssistedPlayers),
+            banner: None,
+            letter_fee: 0,
+            letters_revealed: LookupMap::new(StorageKey::LettersRevealed),
+            clue_solves: LookupMap::new(StorageKey::ClueSolves),
+            eliminated: LookupSet::new(StorageKey::Eliminated),
+            notes: LookupMap::new(StorageKey::Notes),
+            revealed_positions: LookupMap::new(StorageKey::RevealedPositions),
+            letter_requests: LookupMap::new(StorageKey::LetterRequests),
+            letter_escrow: 0,
+            removed_puzzles: LookupSet::new(StorageKey::RemovedPuzzles),
         }
     }
-    
-    // Immutable function. 
-    pub fn get_solution(&self) -> String {
-        self.crossword_solution.clone()
+
+    // Mutable function, only the contract account itself (i.e. right after redeploying). Upgrades a contract
+    // deployed with the first version, which only stored the solution, to the current state layout with
+    // `owner_id` as its owner. Everything else starts as in `new`.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate(owner_id: AccountId) -> Self {
+        let old: OldContract = env::state_read().expect("No state to migrate");
+        let mut contract = Self::new(old.crossword_solution);
+        contract.owner_id = owner_id.clone();
+        contract.init_params.owner_id = owner_id;
+        contract
     }
 
-    // Mutable function requires a signed transaction. Now fn returns a bool type (not String)!
-    pub fn guess_solution(&mut self, solution: String) -> bool {
-        let hashed_input = env::sha256(solution.as_bytes());
-        let hashed_input_hex = hex::encode(&hashed_input);
+    // Mutable function, owner only. Sets the fee (in yoctoNEAR) required with every guess.
+    pub fn set_guess_fee(&mut self, guess_fee: U128) {
+        self.assert_owner();
+        self.guess_fee = guess_fee.0;
+    }
 
-        if hashed_input_hex == self.crossword_solution {
-            env::log_str("You guessed right!");
-            true
+    // Mutable function, owner only. For deflationary events: guess fees are sent to an unspendable account
+    // instead of being collected. Burned fees are gone, so they aren't refunded if a puzzle is cancelled.
+    pub fn set_burn_fees(&mut self, burn_fees: bool) {
+        self.assert_owner();
+        self.burn_fees = burn_fees;
+    }
+
+    // Mutable function, owner only. The contract can't see how old an account is, so to deter sybil spam
+    // players instead prove they hold some NEAR by attaching at least this much (the excess over the fee is refunded).
+    pub fn set_min_stake_to_play(&mut self, min_stake_to_play: U128) {
+        self.assert_owner();
+        self.min_stake_to_play = min_stake_to_play.0;
+    }
+
+    // Mutable function, owner only. Whether to notify winners' accounts when their prize NFT is minted.
+    pub fn set_notify_on_mint(&mut self, notify_on_mint: bool) {
+        self.assert_owner();
+        self.notify_on_mint = notify_on_mint;
+    }
+
+    // Callback, only this contract can call it (#[private]). Logs whether the winner's `nft_on_mint` succeeded.
+    // The token is kept either way, the notification is just a courtesy.
+    #[private]
+    pub fn resolve_mint_notification(&mut self, token_id: TokenId, receiver_id: AccountId) {
+        if near_sdk::is_promise_success() {
+            env::log_str(&format!("{} was notified about token {}", receiver_id, token_id));
         } else {
-            env::log_str("Try again.");
-            false
+            env::log_str(&format!("Could not notify {} about token {}", receiver_id, token_id));
         }
     }
-}
 
-// ---------------------------------------------- TESTS ----------------------------------------------------------
-// use the attribute below for unit tests
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use near_sdk::test_utils::{get_logs, VMContextBuilder};
-    use near_sdk::{testing_env, AccountId};
+    // Mutable function. The winner confirms they want to keep their prize NFT.
+    pub fn claim_nft(&mut self, token_id: TokenId) {
+        let mut token = self.tokens.get(&token_id).expect("Token not found");
+        assert_eq!(env::predecessor_account_id(), token.owner_id, "Only the token owner can claim it");
+        self.assert_not_blacklisted(&token.owner_id);
+        token.claimed = true;
+        self.tokens.insert(&token_id, &token);
+    }
 
-    #[test] // note the button below 'Run test' (but for some reason runs all tests, not just current one)
-    fn debug_get_hash() {
-        // Basic set up for a unit test
-        testing_env!(VMContextBuilder::new().build()); // create some basic context for a transaction, then sets up the testing environment.
-        // Using a unit test to rapidly debug and iterate
-        let debug_solution = "near nomicon ref finance";
-        let debug_hash_bytes = env::sha256(debug_solution.as_bytes());
-        let debug_hash_string = hex::encode(debug_hash_bytes);
-        println!("Let's debug: {:?}", debug_hash_string); // Let's debug: "69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f"
+    // Mutable function, owner only. Takes back a prize NFT nobody claimed within NFT_RECLAIM_DELAY_NS of the mint.
+    pub fn reclaim_unclaimed_nft(&mut self, token_id: TokenId) {
+        self.assert_owner();
+        let mut token = self.tokens.get(&token_id).expect("Token not found");
+        assert!(!token.claimed, "Token was claimed");
+        assert!(
+            env::block_timestamp() >= token.minted_at + NFT_RECLAIM_DELAY_NS,
+            "Token can't be reclaimed yet"
+        );
+        env::log_str(&format!("Token {} reclaimed from {}", token_id, token.owner_id));
+        token.owner_id = self.owner_id.clone();
+        token.approved_account_ids.clear();
+        self.tokens.insert(&token_id, &token);
     }
-    
-    // This get_context is typically included in all unit tests, i.e. set up a mock context:
-    fn get_context(predecessor: AccountId) -> VMContextBuilder { // provide a `predecessor` here, it'll modify the default context
-        let mut builder = VMContextBuilder::new();
-        builder.predecessor_account_id(predecessor);
-        builder
+
+    // Payable function, needs exactly 1 yoctoNEAR (a full access key confirmation, like NEP-171).
+    // The owner or an approved account moves a prize NFT to `receiver_id`. Soulbound tokens can't move.
+    // Moving a token counts as claiming it, so the contract owner can't reclaim it from the receiver.
+    #[payable]
+    pub fn nft_transfer(&mut self, receiver_id: AccountId, token_id: TokenId, memo: Option<String>) {
+        near_sdk::assert_one_yocto();
+        let mut token = self.tokens.get(&token_id).expect("Token not found");
+        assert!(!token.soulbound, "Token is soulbound");
+        let sender = env::predecessor_account_id();
+        assert!(
+            sender == token.owner_id || token.approved_account_ids.contains(&sender),
+            "Only the token owner or an approved account can transfer it"
+        );
+        assert_ne!(token.owner_id, receiver_id, "The token owner and the receiver should be different");
+        env::log_str(&format!(
+            "Token {} transferred from {} to {}{}",
+            token_id,
+            token.owner_id,
+            receiver_id,
+            memo.map(|memo| format!(", memo: {}", memo)).unwrap_or_default()
+        ));
+        token.owner_id = receiver_id;
+        token.approved_account_ids.clear();
+        token.claimed = true;
+        self.tokens.insert(&token_id, &token);
     }
-    
-    #[test]
-    fn check_guess_solution() {
-        // Get Alice as an account ID
-        let alice = AccountId::new_unchecked("alice.testnet".to_string());
-        // Set up the testing context and unit test environment
-        let context = get_context(alice);
-        testing_env!(context.build());
 
-        // Set up contract object and call the new method
-        let mut contract = Contract::new(
-            "69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string(), // near nomicon ref finance 69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f
+    // Payable function, needs at least 1 yoctoNEAR. Lets `account_id` transfer the caller's prize NFT,
+    // e.g. a marketplace. Soulbound tokens can't be approved.
+    #[payable]
+    pub fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId) {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let mut token = self.tokens.get(&token_id).expect("Token not found");
+        assert!(!token.soulbound, "Token is soulbound");
+        assert_eq!(env::predecessor_account_id(), token.owner_id, "Only the token owner can approve");
+        if !token.approved_account_ids.contains(&account_id) {
+            token.approved_account_ids.push(account_id);
+        }
+        self.tokens.insert(&token_id, &token);
+    }
+
+    // Mutable function, owner only. Whether prize NFTs minted for the puzzle from now on are soulbound.
+    pub fn set_soulbound_prizes(&mut self, puzzle_id: String, soulbound_prizes: bool) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.soulbound_prizes = soulbound_prizes;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Discourages grab-and-run: rewards won after this call are released over `vesting_ns`.
+    pub fn set_vesting(&mut self, vesting_ns: u64) {
+        self.assert_owner();
+        self.vesting_ns = vesting_ns;
+    }
+
+    // Mutable function, owner only. Gives a puzzle's first solver a random bonus: their payout is multiplied by a
+    // value between `min` and `max` (in basis points, 10_000 = 1x). Room for the largest bonus is kept in the pool,
+    // so without luck the winner gets pool * 10_000 / max and the rest stays for the next winner.
+    // Note: `env::random_seed()` is the same for every call within a block and can be nudged by block producers,
+    // so it is only unpredictable within limits. Fine for a bit of fun, don't use it for large sums. `None` disables.
+    pub fn set_random_bonus(&mut self, bonus_bps: Option<(u32, u32)>) {
+        self.assert_owner();
+        if let Some((min, max)) = bonus_bps {
+            assert!(10_000 <= min && min <= max, "Expected 10000 <= min <= max");
+        }
+        self.bonus_bps = bonus_bps;
+    }
+
+    // Mutable function, owner only. Stops whales from winning everything: once an account has won this much
+    // in total it gets nothing more. The part of a reward above the cap stays in the pool. `None` removes the cap.
+    pub fn set_max_reward_per_account(&mut self, max_reward_per_account: Option<U128>) {
+        self.assert_owner();
+        self.max_reward_per_account = max_reward_per_account.map(|max| max.0);
+    }
+
+    // Mutable function, owner only. A solver whose reward would be below `min_payout` still wins the puzzle,
+    // but the reward stays in the pool for the next winner.
+    pub fn set_min_payout(&mut self, min_payout: U128) {
+        self.assert_owner();
+        self.min_payout = min_payout.0;
+    }
+
+    // Immutable function. Total rewards the account has won, in yoctoNEAR.
+    pub fn get_earnings(&self, account_id: AccountId) -> U128 {
+        U128(self.earnings.get(&account_id).unwrap_or(0))
+    }
+
+    // Mutable function, owner only. From now on `rollover_bps` of every payout goes into the pool of
+    // `next_puzzle_id` (e.g. 2_000: the winners get 80%, the next puzzle 20%). `None` stops the rollover.
+    pub fn set_rollover(&mut self, rollover_bps: u32, next_puzzle_id: Option<String>) {
+        self.assert_owner();
+        assert!(rollover_bps <= 10_000, "Rollover can't be more than 10000 bps");
+        if let Some(next_puzzle_id) = &next_puzzle_id {
+            let next_puzzle = self.get_puzzle(next_puzzle_id);
+            assert!(next_puzzle.reward_token.is_none(), "Rollover can only go to a puzzle paid in NEAR");
+        }
+        self.rollover_bps = rollover_bps;
+        self.rollover_puzzle = next_puzzle_id;
+    }
+
+    // Mutable function. Transfers whatever part of the caller's vesting reward has been released so far.
+    pub fn claim_vested(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+        self.assert_not_blacklisted(&account_id);
+        let mut entitlement = self.vesting.get(&account_id).expect("Nothing is vesting for this account");
+        let elapsed = env::block_timestamp().saturating_sub(entitlement.start).min(entitlement.duration);
+        // work in basis points so the multiplication can't overflow a u128
+        let vested_bps = elapsed as u128 * 10_000 / entitlement.duration as u128;
+        let vested = entitlement.total * vested_bps / 10_000;
+        let claimable = vested - entitlement.claimed;
+        if claimable > 0 {
+            Promise::new(account_id.clone()).transfer(claimable);
+        }
+        entitlement.claimed = vested;
+        if entitlement.claimed == entitlement.total {
+            self.vesting.remove(&account_id);
+        } else {
+            self.vesting.insert(&account_id, &entitlement);
+        }
+        U128(claimable)
+    }
+
+    // Immutable function. The settings recorded by `new`, whatever has been changed since.
+    pub fn init_params(&self) -> InitParams {
+        self.init_params.clone()
+    }
+
+    // Immutable function. Block timestamp (ns) at which the contract was initialized.
+    pub fn created_at(&self) -> U64 {
+        U64(self.created_at)
+    }
+
+    // Immutable function. Nanoseconds since the contract was initialized, for dashboards.
+    pub fn age_ns(&self) -> u64 {
+        env::block_timestamp() - self.created_at
+    }
+
+    // Mutable function, owner only. Purely informational, `None` clears the banner.
+    pub fn set_banner(&mut self, banner: Option<String>) {
+        self.assert_owner();
+        if let Some(banner) = &banner {
+            assert!(banner.len() <= MAX_BANNER_LEN, "Banner is too long, the limit is {} bytes", MAX_BANNER_LEN);
+        }
+        self.banner = banner;
+    }
+
+    // Immutable function.
+    pub fn get_banner(&self) -> Option<String> {
+        self.banner.clone()
+    }
+
+    // Immutable function. For wallets: the NEP standards this contract implements, e.g. "nep297".
+    pub fn supported_standards(&self) -> Vec<String> {
+        SUPPORTED_STANDARDS.iter().map(|standard| standard.to_string()).collect()
+    }
+
+    // Immutable function. Cheap liveness check reporting key invariants. Unlike `balance_breakdown` it doesn't
+    // walk the catalog, so puzzles' own pools are not part of `balance_ok`.
+    pub fn health(&self) -> HealthReport {
+        let storage_stake = env::storage_usage() as Balance * env::storage_byte_cost();
+        HealthReport {
+            initialized: true, // an uninitialized contract panics (PanicOnDefault) instead of getting here
+            paused: self.is_paused(),
+            puzzle_count: self.puzzles.len(),
+            balance_ok: env::account_balance() >= storage_stake + self.prize_pool + self.collected_fees + self.letter_escrow,
+        }
+    }
+
+    // Immutable function. Hex sha256 over the Borsh encoding of the crossword's solution hash, the shared pool and,
+    // for every puzzle sorted by id, its solution hash, solvers in order and pool. Two RPC nodes returning
+    // different fingerprints for the same block disagree on state. The crossword's solvers aren't iterable
+    // (LookupSet) so they're not covered. Walks the whole catalog.
+    pub fn state_fingerprint(&self) -> String {
+        let mut puzzles: Vec<(String, String, Vec<AccountId>, Balance)> = self
+            .puzzles
+            .iter()
+            .map(|(puzzle_id, puzzle)| {
+                let solvers = puzzle.solve_feed.iter().map(|(solver, _)| solver).collect();
+                (puzzle_id, puzzle.solution_hash, solvers, puzzle.prize_pool)
+            })
+            .collect();
+        puzzles.sort_by(|a, b| a.0.cmp(&b.0));
+        let state = (&self.crossword_solution, self.prize_pool, puzzles);
+        let bytes = state.try_to_vec().expect("Failed to serialize state");
+        hex::encode(env::sha256(&bytes))
+    }
+
+    // Immutable function. Approximate staking cost in yoctoNEAR of `add_puzzle` with this meta followed by
+    // adding `num_clues` clues. Long puzzle ids, hints and Blake2b hashes take somewhat more.
+    pub fn estimate_puzzle_storage(&self, meta: PuzzleMeta, num_clues: u16) -> U128 {
+        let meta_bytes = meta.try_to_vec().expect("Failed to serialize meta").len() as u64;
+        let bytes = PUZZLE_BASE_BYTES + meta_bytes + num_clues as u64 * CLUE_BYTES;
+        U128(bytes as Balance * env::storage_byte_cost())
+    }
+
+    // Payable function: anyone can add the attached NEAR to the prize pool.
+    #[payable]
+    pub fn fund_prize_pool(&mut self) {
+        self.prize_pool += env::attached_deposit();
+    }
+
+    // Immutable function. For treasury planning: the NEAR held in puzzles' own pools (pools in tokens and the
+    // shared pool aren't included). Walks the whole catalog.
+    pub fn total_committed_prizes(&self) -> U128 {
+        let puzzle_pools = self
+            .puzzles
+            .values()
+            .filter(|puzzle| puzzle.reward_token.is_none())
+            .map(|puzzle| puzzle.prize_pool)
+            .sum();
+        U128(puzzle_pools)
+    }
+
+    // Immutable function, owner only (so it must be called as a transaction). Panics if the puzzles' pools and
+    // the shared pool together are more than the balance left after storage, collected fees and letter escrow.
+    pub fn assert_prizes_covered(&self) {
+        self.assert_owner();
+        let storage_stake = env::storage_usage() as Balance * env::storage_byte_cost();
+        let available = env::account_balance()
+            .saturating_sub(self.collected_fees)
+            .saturating_sub(self.letter_escrow)
+            .saturating_sub(storage_stake);
+        let committed = self.prize_pool + self.total_committed_prizes().0;
+        assert!(committed <= available, "Committed prizes of {} exceed the free balance of {}", committed, available);
+    }
+
+    // Immutable function. Splits the contract balance into what is owed to winners, what was earned
+    // from fees, what is held for letters the host hasn't opened yet, what is locked to pay for storage,
+    // and whatever is left over.
+    // The prize pool includes the puzzles' own pools, so this walks the whole catalog.
+    pub fn balance_breakdown(&self) -> BalanceBreakdown {
+        let total = env::account_balance();
+        let storage_stake = env::storage_usage() as Balance * env::storage_byte_cost();
+        let prize_pool = self.prize_pool + self.total_committed_prizes().0;
+        let free = total
+            .saturating_sub(prize_pool)
+            .saturating_sub(self.collected_fees)
+            .saturating_sub(self.letter_escrow)
+            .saturating_sub(storage_stake);
+        BalanceBreakdown {
+            total: U128(total),
+            prize_pool: U128(prize_pool),
+            collected_fees: U128(self.collected_fees),
+            letter_escrow: U128(self.letter_escrow),
+            storage_stake: U128(storage_stake),
+            free: U128(free),
+        }
+    }
+
+    // Payable function, owner only. Registers a puzzle and makes the attached deposit its own prize pool.
+    #[payable]
+    pub fn add_funded_puzzle(&mut self, puzzle_id: String, solution_hash: String, meta: PuzzleMeta) {
+        let prize = env::attached_deposit();
+        assert!(prize > 0, "Attach the prize for the puzzle");
+        self.add_puzzle(puzzle_id.clone(), solution_hash, meta);
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.prize_pool = prize;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Makes the puzzle pay its prize in a fungible token instead of NEAR.
+    // Fund it by `ft_transfer_call`ing the tokens to this contract with the puzzle id as `msg`.
+    // Token prizes are paid straight from the puzzle's own pool: no random bonus, rollover, link, vesting or cap.
+    pub fn set_reward_token(&mut self, puzzle_id: String, reward_token: Option<AccountId>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert_eq!(puzzle.prize_pool, 0, "Can't change the currency of a funded prize pool");
+        assert!(puzzle.reward_mode != RewardMode::None, "Puzzle has no reward");
+        assert!(self.rollover_puzzle.as_ref() != Some(&puzzle_id), "Puzzle receives the NEAR rollover");
+        puzzle.reward_token = reward_token;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // NEP-141 receiver: adds tokens sent with `ft_transfer_call` to the prize pool of the puzzle named in `msg`.
+    // Returns how many tokens to refund, none unless the puzzle doesn't use this token.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
+        let token = env::predecessor_account_id();
+        let mut puzzle = match self.puzzles.get(&msg) {
+            Some(puzzle) if puzzle.reward_token.as_ref() == Some(&token) => puzzle,
+            _ => {
+                env::log_str(&format!("Refunding {}: puzzle {} doesn't take {} tokens", sender_id, msg, token));
+                return amount;
+            }
+        };
+        puzzle.prize_pool += amount.0;
+        self.puzzles.insert(&msg, &puzzle);
+        U128(0)
+    }
+
+    // Mutable function, owner only. For a grand finale: solving `puzzle_id` also pays out the pool of
+    // `linked_puzzle_id`. `None` removes the link.
+    pub fn set_linked_puzzle(&mut self, puzzle_id: String, linked_puzzle_id: Option<String>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        if let Some(linked_puzzle_id) = &linked_puzzle_id {
+            assert_ne!(linked_puzzle_id, &puzzle_id, "A puzzle can't be linked to itself");
+            self.get_puzzle(linked_puzzle_id);
+        }
+        puzzle.linked_puzzle = linked_puzzle_id;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function. The puzzle's own prize pool in yoctoNEAR, 0 if it pays from the shared pool.
+    pub fn puzzle_prize_pool(&self, puzzle_id: String) -> U128 {
+        U128(self.get_puzzle(&puzzle_id).prize_pool)
+    }
+
+    // Mutable function, owner only. Registers a new (empty, unfrozen) puzzle. Ids of removed puzzles can't be reused.
+    pub fn add_puzzle(&mut self, puzzle_id: String, solution_hash: String, meta: PuzzleMeta) {
+        self.assert_owner();
+        assert!(self.puzzles.get(&puzzle_id).is_none(), "Puzzle already exists");
+        assert!(!self.removed_puzzles.contains(&puzzle_id), "Puzzle id was used by a removed puzzle");
+        validate_meta(&meta);
+        if let Some(existing_id) = self.solution_hashes.get(&solution_hash) {
+            assert!(!self.unique_solutions, "Solution hash is already used by puzzle {}", existing_id);
+        }
+        self.solution_hashes.insert(&solution_hash, &puzzle_id);
+        let puzzle = Puzzle {
+            solution_hash,
+            meta,
+            frozen: false,
+            clues: BTreeMap::new(),
+            deadline: None,
+            start_timestamp: None,
+            solve_feed: Vector::new(StorageKey::SolveFeed {
+                puzzle_id_hash: env::sha256(puzzle_id.as_bytes()),
+            }),
+            participants: UnorderedMap::new(StorageKey::Participants {
+                puzzle_id_hash: env::sha256(puzzle_id.as_bytes()),
+            }),
+            tokens: Vector::new(StorageKey::PuzzleTokens {
+                puzzle_id_hash: env::sha256(puzzle_id.as_bytes()),
+            }),
+            cancelled: false,
+            required_nft: None,
+            last_status: PuzzleStatus::NotStarted,
+            reveal_at: 0,
+            sealed_plaintext: None,
+            author: env::predecessor_account_id(),
+            tips: 0,
+            guess_lengths: BTreeMap::new(),
+            prize_pool: 0,
+            soulbound_prizes: false,
+            normalize_rules: NormalizeRules::default(),
+            last_countdown: None,
+            linked_puzzle: None,
+            paused: false,
+            reward_token: None,
+            allowlist_only: false,
+            access_password_hash: None,
+            reward_mode: RewardMode::FirstOnly,
+            reward_window_ns: 0,
+            window_solvers: vec![],
+            rewards_finalized: false,
+            max_attempts: None,
+            guess_count: 0,
+            practice_mode: false,
+            max_winners: None,
+            rewarded_winners: 0,
+            sudden_death: false,
+            grid_dimensions: None,
+            grid: vec![],
+        };
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        self.update_author_stats(&env::predecessor_account_id(), |stats| stats.puzzles_created += 1);
+        self.sync_status(&puzzle_id);
+    }
+
+    // Mutable function, owner only. Deletes a puzzle nobody solved and which holds no pool, e.g. a draft or a
+    // cancelled puzzle (cancelling refunds the guess fees and releases the pool). No other puzzle may link to it
+    // or roll over into it. Per-account records of the puzzle (allowlist, clue progress) aren't iterable and stay
+    // behind, so the id is kept as a tombstone and `add_puzzle` won't reuse it. Walks the whole catalog.
+    pub fn remove_puzzle(&mut self, puzzle_id: String) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert!(puzzle.solve_feed.is_empty(), "Can't remove a solved puzzle");
+        assert_eq!(puzzle.prize_pool, 0, "Cancel the puzzle first to release its pool");
+        assert!(puzzle.cancelled || puzzle.participants.is_empty(), "Cancel the puzzle first to refund its guesses");
+        assert!(self.rollover_puzzle.as_ref() != Some(&puzzle_id), "Puzzle receives the rollover");
+        assert!(
+            self.puzzles.values().all(|other| other.linked_puzzle.as_ref() != Some(&puzzle_id)),
+            "Another puzzle is linked to this one"
         );
-        contract.guess_solution("wrong answer here".to_string());
-        assert_eq!(get_logs(), ["Try again."], "Expected a failure log."); //Asserts that two expressions are equal to each other 
-        contract.guess_solution("near nomicon ref finance".to_string());
-        //assert!(ans, true); // Asserts that a boolean expression is true at runtime.
-        assert_eq!( 
-            get_logs(), ["Try again.", "You guessed right!"],
-            "Expected a successful log after the previous failed log."
+        puzzle.participants.clear();
+        if self.solution_hashes.get(&puzzle.solution_hash) == Some(puzzle_id.clone()) {
+            self.solution_hashes.remove(&puzzle.solution_hash);
+        }
+        self.puzzles.remove(&puzzle_id);
+        self.removed_puzzles.insert(&puzzle_id);
+        self.update_author_stats(&puzzle.author, |stats| stats.puzzles_created -= 1);
+        env::log_str(&format!("Puzzle {} was removed", puzzle_id));
+    }
+
+    // Immutable function. For author leaderboards: how many of the catalog's puzzles the account is the author of.
+    pub fn author_puzzle_count(&self, author: AccountId) -> u64 {
+        self.author_stats(author).puzzles_created
+    }
+
+    // Mutable function, owner only. For weekly reruns: a new puzzle with the source's meta, clues, grid and settings
+    // (normalization, gates, attempt cap, sudden death, reward mode, token and winner cap), but no solvers, guesses,
+    // pool or start time, and the given deadline. Like `add_puzzle` it fails if solutions must be unique.
+    pub fn clone_puzzle(&mut self, source_id: String, new_id: String, new_deadline: U64) {
+        let source = self.get_puzzle(&source_id);
+        self.add_puzzle(new_id.clone(), source.solution_hash, source.meta);
+        let mut puzzle = self.get_puzzle(&new_id);
+        puzzle.clues = source.clues;
+        puzzle.deadline = Some(new_deadline.0);
+        puzzle.normalize_rules = source.normalize_rules;
+        puzzle.required_nft = source.required_nft;
+        puzzle.allowlist_only = source.allowlist_only;
+        puzzle.access_password_hash = source.access_password_hash;
+        puzzle.soulbound_prizes = source.soulbound_prizes;
+        puzzle.max_attempts = source.max_attempts;
+        puzzle.sudden_death = source.sudden_death;
+        puzzle.grid_dimensions = source.grid_dimensions;
+        puzzle.grid = source.grid;
+        puzzle.reward_mode = source.reward_mode;
+        puzzle.reward_window_ns = source.reward_window_ns;
+        puzzle.reward_token = source.reward_token;
+        puzzle.max_winners = source.max_winners;
+        self.puzzles.insert(&new_id, &puzzle);
+        self.sync_status(&new_id);
+    }
+
+    // Mutable function, owner only. Stored hashes must have been computed with the same algorithm.
+    // Blake2b is refused unless the contract was built with the `blake2` feature.
+    pub fn set_hash_algo(&mut self, hash_algo: HashAlgo) {
+        self.assert_owner();
+        require!(
+            hash_algo != HashAlgo::Blake2b || cfg!(feature = "blake2"),
+            "This contract was built without the blake2 feature"
+        );
+        self.hash_algo = hash_algo;
+    }
+
+    // Mutable function, owner only. Chooses how lenient matching is for the crossword set in `new`.
+    pub fn set_normalization(&mut self, rules: NormalizeRules) {
+        self.assert_owner();
+        assert_supported_rules(&rules);
+        self.normalize_rules = rules;
+    }
+
+    // Mutable function, owner only. Chooses how lenient matching is for one puzzle (its answer and clues).
+    // The stored hash was computed under the old rules and the contract doesn't know the plaintext, so the
+    // solution hash recomputed under the new rules (see `expected_hash`) has to come with the change.
+    // Clue hashes aren't replaced, so pick the rules before adding clues. Only for unfrozen, unsolved puzzles.
+    pub fn set_normalize_rules(&mut self, puzzle_id: String, rules: NormalizeRules, new_hash: String) {
+        self.assert_owner();
+        assert_supported_rules(&rules);
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        assert!(puzzle.solve_feed.is_empty(), "Can't change the answer of a solved puzzle");
+        self.assert_valid_hash(&new_hash);
+        if let Some(existing_id) = self.solution_hashes.get(&new_hash) {
+            assert!(
+                !self.unique_solutions || existing_id == puzzle_id,
+                "Solution hash is already used by puzzle {}",
+                existing_id
+            );
+        }
+        if self.solution_hashes.get(&puzzle.solution_hash) == Some(puzzle_id.clone()) {
+            self.solution_hashes.remove(&puzzle.solution_hash);
+        }
+        self.solution_hashes.insert(&new_hash, &puzzle_id);
+        puzzle.normalize_rules = rules;
+        puzzle.solution_hash = new_hash;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function.
+    pub fn get_normalize_rules(&self, puzzle_id: String) -> NormalizeRules {
+        self.get_puzzle(&puzzle_id).normalize_rules
+    }
+
+    // Immutable function. The hash a correct answer must produce for this puzzle under the current normalization
+    // and algorithm. Lets operators check the hash they stored.
+    pub fn expected_hash(&self, puzzle_id: String, solution: String) -> String {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        self.hash_answer(&solution, &puzzle.normalize_rules)
+    }
+
+    // Mutable function, owner only. Guards against accidentally reusing the same answer across puzzles.
+    pub fn set_unique_solutions(&mut self, unique_solutions: bool) {
+        self.assert_owner();
+        self.unique_solutions = unique_solutions;
+    }
+
+    // Mutable function, owner or author only. Adds one clue to a puzzle that is still being built.
+    // `length_hint` is optional: pass it to let players see how many characters the answer has.
+    // `weight` is the clue's share of the score relative to the other clues.
+    pub fn add_clue(&mut self, puzzle_id: String, clue_number: u16, answer_hash: String, length_hint: Option<u8>, weight: u8) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        assert!(!puzzle.clues.contains_key(&clue_number), "Clue already exists");
+        self.assert_valid_hash(&answer_hash);
+        puzzle.clues.insert(clue_number, Clue { answer_hash, length_hint, weight, hint: None, prerequisites: vec![], bonus: None, letter_commitments: vec![], opened_letters: BTreeMap::new() });
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner or author only. Announces the size of the puzzle's grid.
+    pub fn set_grid_dimensions(&mut self, puzzle_id: String, rows: u16, columns: u16) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        puzzle.grid_dimensions = Some((rows, columns));
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner or author only. Publishes the grid layout (true = fillable cell), see `validate_grid`.
+    pub fn set_grid(&mut self, puzzle_id: String, grid: Vec<Vec<bool>>) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        puzzle.grid = grid;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function. (rows, columns), if the host announced them.
+    pub fn grid_dimensions(&self, puzzle_id: String) -> Option<(u16, u16)> {
+        self.get_puzzle(&puzzle_id).grid_dimensions
+    }
+
+    // Immutable function. Whether the published grid is possible: it has exactly the announced number of rows
+    // and columns, and at least one fillable cell. False if the dimensions were never announced.
+    pub fn validate_grid(&self, puzzle_id: String) -> bool {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        match puzzle.grid_dimensions {
+            Some((rows, columns)) => {
+                puzzle.grid.len() == rows as usize
+                    && puzzle.grid.iter().all(|row| row.len() == columns as usize)
+                    && puzzle.grid.iter().flatten().any(|fillable| *fillable)
+            }
+            None => false,
+        }
+    }
+
+    // Mutable function, owner or author only. Adds or replaces several clues of a puzzle still being built in one
+    // transaction: (clue number, answer hash) pairs. All hashes are checked before anything is stored.
+    // Clues set this way (replaced ones included) are required, have weight 1 and no length hint, hint or prerequisites.
+    pub fn set_clues(&mut self, puzzle_id: String, clues: Vec<(u16, String)>) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        for (_, answer_hash) in clues.iter() {
+            self.assert_valid_hash(answer_hash);
+        }
+        for (clue_number, answer_hash) in clues {
+            let clue = Clue { answer_hash, length_hint: None, weight: 1, hint: None, prerequisites: vec![], bonus: None, letter_commitments: vec![], opened_letters: BTreeMap::new() };
+            puzzle.clues.insert(clue_number, clue);
+        }
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Pre-announces a puzzle: its meta can be read, but guesses wait for the start.
+    // `None` opens it right away.
+    pub fn set_puzzle_start(&mut self, puzzle_id: String, start_timestamp: Option<U64>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.start_timestamp = start_timestamp.map(|s| s.0);
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        self.sync_status(&puzzle_id);
+    }
+
+    // Mutable function, owner or author only. Sets the hint players see after failing the clue too often.
+    pub fn set_clue_hint(&mut self, puzzle_id: String, clue_number: u16, hint: String) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        let clue = puzzle.clues.get_mut(&clue_number).expect("Clue not found");
+        clue.hint = Some(hint);
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner or author only. For narrative puzzles: the clue can only be answered once all
+    // `prerequisites` are solved. An empty list removes the dependency.
+    pub fn set_clue_prerequisites(&mut self, puzzle_id: String, clue_number: u16, prerequisites: Vec<u16>) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        for prerequisite in prerequisites.iter() {
+            assert!(*prerequisite != clue_number, "A clue can't depend on itself");
+            assert!(puzzle.clues.contains_key(prerequisite), "Prerequisite clue {} not found", prerequisite);
+        }
+        let clue = puzzle.clues.get_mut(&clue_number).expect("Clue not found");
+        clue.prerequisites = prerequisites;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. `Some(bonus)` makes the clue optional: it isn't needed to complete the puzzle,
+    // but that much (yoctoNEAR) of the pool is only paid to winners who solved it. `None` makes it required again.
+    pub fn set_clue_bonus(&mut self, puzzle_id: String, clue_number: u16, bonus: Option<U128>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        let clue = puzzle.clues.get_mut(&clue_number).expect("Clue not found");
+        clue.bonus = bonus.map(|bonus| bonus.0);
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function. Whether the account solved every required (non-optional) clue of the puzzle, which
+    // counts as solving the puzzle. Always false for a puzzle without required clues.
+    pub fn is_fully_solved(&self, account_id: AccountId, puzzle_id: String) -> bool {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let mut required = puzzle.clues.iter().filter(|(_, clue)| clue.bonus.is_none()).peekable();
+        required.peek().is_some()
+            && required.all(|(clue_number, _)| self.solved_clues.contains(&(puzzle_id.clone(), account_id.clone(), *clue_number)))
+    }
+
+    // Immutable function. Whether the account solved every prerequisite of the clue, so it can answer it.
+    pub fn is_clue_unlocked(&self, account_id: AccountId, puzzle_id: String, clue: u16) -> bool {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let clue = puzzle.clues.get(&clue).expect("Clue not found");
+        clue.prerequisites
+            .iter()
+            .all(|prerequisite| self.solved_clues.contains(&(puzzle_id.clone(), account_id.clone(), *prerequisite)))
+    }
+
+    // Mutable function, owner only. Number of wrong answers to a clue which unlocks its hint, 0 turns hints off.
+    pub fn set_hint_after_failures(&mut self, hint_after_failures: u32) {
+        self.assert_owner();
+        self.hint_after_failures = hint_after_failures;
+    }
+
+    // Mutable function, owner only. Accessible mode: a clue the player failed `after_failures` times is marked
+    // solved for them so they can progress. A player helped this way can still solve the puzzle, but is never
+    // paid or minted a prize for it.
+    pub fn set_accessibility_mode(&mut self, enabled: bool, after_failures: u32) {
+        self.assert_owner();
+        assert!(!enabled || after_failures > 0, "Clues can only be auto-solved after at least one failure");
+        self.accessibility_mode = enabled;
+        self.auto_solve_after_failures = after_failures;
+    }
+
+    // Mutable function, owner or author only. Lets players buy single letters of a clue's answer with
+    // `request_letter` and `reveal_letter`: one commitment per position, the hex sha256 of the letter followed by a secret salt
+    // (e.g. sha256("m" + salt)). The plaintext never enters contract state until the host opens a letter
+    // someone paid for with `open_letter`. Only until the puzzle is frozen or a letter of the clue was sold,
+    // since players paid for letters of the current commitments.
+    pub fn set_clue_letters(&mut self, puzzle_id: String, clue_number: u16, letter_commitments: Vec<String>) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        for commitment in letter_commitments.iter() {
+            assert!(
+                commitment.len() == 64 && commitment.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+                "Invalid commitment {}, expected 64 lowercase hex characters",
+                commitment
+            );
+        }
+        let clue = puzzle.clues.get_mut(&clue_number).expect("Clue not found");
+        let requested = (0..clue.letter_commitments.len() as u32)
+            .any(|position| self.letter_requests.contains_key(&(puzzle_id.clone(), clue_number, position)));
+        assert!(clue.opened_letters.is_empty() && !requested, "Letters of this clue were already revealed");
+        clue.letter_commitments = letter_commitments;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner or author only. Answers a `letter_requested` event: publishes the letter at
+    // `position` with the salt of its commitment, and moves the fees held for it into the collected fees.
+    // Once opened, the letter is public (it's in this call's arguments), but `revealed_letter` only returns
+    // it to accounts which paid for it.
+    pub fn open_letter(&mut self, puzzle_id: String, clue_number: u16, position: u32, letter: String, salt: String) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert_eq!(letter.chars().count(), 1, "Open exactly one letter");
+        let clue = puzzle.clues.get_mut(&clue_number).expect("Clue not found");
+        let commitment = clue.letter_commitments.get(position as usize).expect("Position is past the end of the answer");
+        let opened = hex::encode(env::sha256(format!("{}{}", letter, salt).as_bytes()));
+        assert_eq!(&opened, commitment, "Letter and salt don't match the commitment");
+        clue.opened_letters.insert(position, letter);
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        if let Some(requests) = self.letter_requests.remove(&(puzzle_id, clue_number, position)) {
+            let fees: Balance = requests.iter().map(|(_, fee, _)| fee).sum();
+            self.letter_escrow -= fees;
+            self.collected_fees += fees;
+        }
+    }
+
+    // Mutable function, owner only. Fee (in yoctoNEAR) for the first letter an account reveals on a puzzle.
+    pub fn set_letter_fee(&mut self, letter_fee: U128) {
+        self.assert_owner();
+        self.letter_fee = letter_fee.0;
+    }
+
+    // Immutable function. What the account's next `request_letter` or `reveal_letter` on the puzzle costs.
+    pub fn next_letter_fee(&self, puzzle_id: String, account_id: AccountId) -> U128 {
+        let revealed = self.letters_revealed.get(&(puzzle_id, account_id)).unwrap_or(0);
+        U128(self.letter_fee.saturating_mul(2u128.saturating_pow(revealed)))
+    }
+
+    // Payable function. A granular hint: buys the character at `position` (0-based) of the clue's answer before
+    // the host opened it. The fee doubles with every letter the account reveals on the puzzle (see
+    // `next_letter_fee`), any excess is refunded. The fee is held until the host answers the `letter_requested`
+    // event with `open_letter`, and the letter then shows up in `revealed_letter`. If the host doesn't open it
+    // within a day, `refund_letter` gives the fee back. `password` is only needed for puzzles with an access password.
+    #[payable]
+    pub fn request_letter(&mut self, puzzle_id: String, clue: u16, position: u32, password: Option<String>) {
+        let player = env::predecessor_account_id();
+        let (fee, letter) = self.buy_letter(&player, &puzzle_id, clue, position, password.as_deref());
+        assert!(letter.is_none(), "Letter is already open, buy it with reveal_letter");
+        let key = (puzzle_id.clone(), clue, position);
+        let mut requests = self.letter_requests.get(&key).unwrap_or_default();
+        requests.push((player.clone(), fee, env::block_timestamp()));
+        self.letter_requests.insert(&key, &requests);
+        self.letter_escrow += fee;
+        log_event(
+            "letter_requested",
+            json!({ "puzzle_id": puzzle_id, "clue": clue, "position": position, "account_id": player }),
         );
     }
+
+    // Payable function. Buys a letter the host already opened with `open_letter` (for another player's
+    // `request_letter`) and returns it straight away. Same escalating fee as `request_letter`.
+    #[payable]
+    pub fn reveal_letter(&mut self, puzzle_id: String, clue: u16, position: u32, password: Option<String>) -> char {
+        let player = env::predecessor_account_id();
+        let (fee, letter) = self.buy_letter(&player, &puzzle_id, clue, position, password.as_deref());
+        let letter = letter.expect("Letter isn't open yet, ask the host for it with request_letter");
+        self.collected_fees += fee;
+        letter
+    }
+
+    // Mutable function. Takes back the fee of a `request_letter` the host didn't answer with `open_letter`
+    // within a day, or straight away if the puzzle was cancelled or removed. The letter then no longer
+    // counts towards the account's next fee.
+    pub fn refund_letter(&mut self, puzzle_id: String, clue: u16, position: u32) -> Promise {
+        let player = env::predecessor_account_id();
+        let key = (puzzle_id.clone(), clue, position);
+        let mut requests = self.letter_requests.get(&key).unwrap_or_default();
+        let index = requests
+            .iter()
+            .position(|(account_id, _, _)| account_id == &player)
+            .expect("No pending request for this letter");
+        let (_, fee, requested_at) = requests.remove(index);
+        let cancelled = self.puzzles.get(&puzzle_id).map(|puzzle| puzzle.cancelled).unwrap_or(true);
+        let deadline = requested_at + LETTER_REFUND_DELAY_NS;
+        assert!(cancelled || env::block_timestamp() >= deadline, "The host has until {} to open the letter", deadline);
+        if requests.is_empty() {
+            self.letter_requests.remove(&key);
+        } else {
+            self.letter_requests.insert(&key, &requests);
+        }
+        self.letter_escrow -= fee;
+        let account_key = (puzzle_id, player.clone());
+        let mut positions = self.revealed_positions.get(&account_key).unwrap_or_default();
+        positions.retain(|bought| bought != &(clue, position));
+        self.revealed_positions.insert(&account_key, &positions);
+        let revealed = self.letters_revealed.get(&account_key).unwrap_or(0);
+        self.letters_revealed.insert(&account_key, &revealed.saturating_sub(1));
+        Promise::new(player).transfer(fee)
+    }
+
+    // Shared by `request_letter` and `reveal_letter`: runs the access checks, takes the escalating fee (refunding
+    // any excess) and records the letter as bought. Returns the fee and the letter if the host already opened it.
+    fn buy_letter(
+        &mut self,
+        player: &AccountId,
+        puzzle_id: &str,
+        clue: u16,
+        position: u32,
+        password: Option<&str>,
+    ) -> (Balance, Option<char>) {
+        self.assert_can_guess(player, Some(puzzle_id), password);
+        let puzzle = self.get_puzzle(puzzle_id);
+        let clue_info = puzzle.clues.get(&clue).expect("Clue not found");
+        assert!(!clue_info.letter_commitments.is_empty(), "Letters aren't available for this clue");
+        assert!((position as usize) < clue_info.letter_commitments.len(), "Position is past the end of the answer");
+        let key = (puzzle_id.to_string(), player.clone());
+        let mut positions = self.revealed_positions.get(&key).unwrap_or_default();
+        let index = positions.binary_search(&(clue, position)).expect_err("Letter was already revealed");
+        let fee = self.next_letter_fee(puzzle_id.to_string(), player.clone()).0;
+        let deposit = env::attached_deposit();
+        assert!(deposit >= fee, "Attach at least {} yoctoNEAR to reveal a letter", fee);
+        if deposit > fee {
+            Promise::new(player.clone()).transfer(deposit - fee);
+        }
+        let revealed = self.letters_revealed.get(&key).unwrap_or(0);
+        self.letters_revealed.insert(&key, &(revealed + 1));
+        positions.insert(index, (clue, position));
+        self.revealed_positions.insert(&key, &positions);
+        (fee, clue_info.opened_letters.get(&position).and_then(|letter| letter.chars().next()))
+    }
+
+    // Immutable function. The letter at `position` if the account paid for it and the host opened it.
+    pub fn revealed_letter(&self, account_id: AccountId, puzzle_id: String, clue: u16, position: u32) -> Option<char> {
+        let positions = self.revealed_positions.get(&(puzzle_id.clone(), account_id)).unwrap_or_default();
+        if positions.binary_search(&(clue, position)).is_err() {
+            return None;
+        }
+        let puzzle = self.get_puzzle(&puzzle_id);
+        puzzle
+            .clues
+            .get(&clue)
+            .and_then(|clue| clue.opened_letters.get(&position))
+            .and_then(|letter| letter.chars().next())
+    }
+
+    // Immutable function. For restoring the UI: the index of every letter the account paid to reveal on the
+    // puzzle with `request_letter` or `reveal_letter`, sorted. Indices run over the answers' letters with the
+    // clues in ascending order, e.g. with a 4-letter clue 1, position 3 of clue 2 is index 7.
+    pub fn unlocked_hints(&self, account_id: AccountId, puzzle_id: String) -> Vec<usize> {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let mut offsets = BTreeMap::new();
+        let mut offset = 0;
+        for (clue_number, clue) in puzzle.clues.iter() {
+            offsets.insert(*clue_number, offset);
+            offset += clue.letter_commitments.len();
+        }
+        self.revealed_positions
+            .get(&(puzzle_id, account_id))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(clue, position)| offsets.get(clue).map(|offset| offset + *position as usize))
+            .collect()
+    }
+
+    // Immutable function. (clue number, hint) for every hint the account has unlocked on the puzzle.
+    pub fn available_hints(&self, account_id: AccountId, puzzle_id: String) -> Vec<(u16, String)> {
+        if self.hint_after_failures == 0 {
+            return vec![];
+        }
+        let puzzle = self.get_puzzle(&puzzle_id);
+        puzzle
+            .clues
+            .into_iter()
+            .filter(|(clue_number, _)| {
+                let key = (puzzle_id.clone(), account_id.clone(), *clue_number);
+                self.attempts_per_clue.get(&key).unwrap_or(0) >= self.hint_after_failures
+            })
+            .filter_map(|(clue_number, clue)| clue.hint.map(|hint| (clue_number, hint)))
+            .collect()
+    }
+
+    // Mutable function, owner only. `None` removes the deadline.
+    pub fn set_puzzle_deadline(&mut self, puzzle_id: String, deadline: Option<U64>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.deadline = deadline.map(|d| d.0);
+        puzzle.last_countdown = None; // a new deadline gets its own countdown
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        self.sync_status(&puzzle_id);
+    }
+
+    // Mutable function, owner only. Only the first `max_winners` solvers are paid, later ones still enter the
+    // solve feed (and get a prize NFT). `None` pays everyone while the pool lasts.
+    pub fn set_max_winners(&mut self, puzzle_id: String, max_winners: Option<u32>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.max_winners = max_winners;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Reopens rewards on a puzzle which already paid its winners: the next
+    // `additional` new solvers are paid too. Solvers who weren't paid before stay unpaid.
+    pub fn increase_max_winners(&mut self, puzzle_id: String, additional: u32) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        let max_winners = puzzle.max_winners.expect("Puzzle has no winner limit");
+        puzzle.max_winners = Some(max_winners.checked_add(additional).expect("Winner limit overflow"));
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Keeps a (typically solved) puzzle playable for practice: guesses are still
+    // checked, but a correct one doesn't enter the solve feed and is never paid or minted a prize.
+    pub fn set_practice_mode(&mut self, puzzle_id: String, enabled: bool) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.practice_mode = enabled;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Hardcore play: a wrong `guess_solution` or `guess_clue` eliminates the account
+    // from the puzzle, after which it can't guess the solution or answer clues on it any more.
+    pub fn set_sudden_death(&mut self, puzzle_id: String, enabled: bool) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.sudden_death = enabled;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function.
+    pub fn is_eliminated(&self, account_id: AccountId, puzzle_id: String) -> bool {
+        self.eliminated.contains(&(puzzle_id, account_id))
+    }
+
+    // Mutable function, owner only. Caps the guesses everyone together can make on the puzzle, `None` removes the cap.
+    pub fn set_max_attempts(&mut self, puzzle_id: String, max_attempts: Option<u64>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.max_attempts = max_attempts;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function. Guesses left before the puzzle closes, so the UI can warn players. None = no cap.
+    pub fn global_attempts_remaining(&self, puzzle_id: String) -> Option<u64> {
+        self.get_puzzle(&puzzle_id).attempts_remaining()
+    }
+
+    // Immutable function.
+    pub fn puzzle_status(&self, puzzle_id: String) -> PuzzleStatus {
+        self.get_puzzle(&puzzle_id).status()
+    }
+
+    // Mutable function, anyone can call it. Contracts can't run timers, so time based transitions (like expiry)
+    // are only noticed when someone looks. This emits the event for such a transition if it wasn't emitted yet.
+    pub fn refresh_status(&mut self, puzzle_id: String) -> PuzzleStatus {
+        self.sync_status(&puzzle_id)
+    }
+
+    // Mutable function, owner only. Credits a guest author, who will receive the puzzle's tips.
+    pub fn set_author(&mut self, puzzle_id: String, author: AccountId) {
+        self.assert_owner();
+        self.change_author(&puzzle_id, author);
+    }
+
+    // Mutable function, author only. Hands the puzzle off to another host, who then receives its tips and can
+    // edit it until it's frozen (and the previous author no longer can).
+    pub fn transfer_puzzle(&mut self, puzzle_id: String, new_author: AccountId) {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        assert_eq!(env::predecessor_account_id(), puzzle.author, "Only the puzzle's author can transfer it");
+        self.change_author(&puzzle_id, new_author);
+    }
+
+    // Payable function: anyone can tip the puzzle's author, the attached NEAR is forwarded to them.
+    #[payable]
+    pub fn tip_author(&mut self, puzzle_id: String) {
+        let tip = env::attached_deposit();
+        assert!(tip > 0, "Attach a tip");
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.tips += tip;
+        Promise::new(puzzle.author.clone()).transfer(tip);
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        self.update_author_stats(&puzzle.author, |stats| stats.total_tips.0 += tip);
+    }
+
+    // Immutable function. Reputation of an author: how many puzzles they have, how often those were solved
+    // and how much they were tipped. All zero for an account which never authored a puzzle.
+    pub fn author_stats(&self, author: AccountId) -> AuthorStats {
+        self.author_stats.get(&author).unwrap_or(AuthorStats {
+            puzzles_created: 0,
+            total_solvers: 0,
+            total_tips: U128(0),
+        })
+    }
+
+    // Immutable function. Total tipped to the puzzle's author, in yoctoNEAR.
+    pub fn total_tips(&self, puzzle_id: String) -> U128 {
+        U128(self.get_puzzle(&puzzle_id).tips)
+    }
+
+    // Mutable function, owner only. Schedules the reveal of the solution at `reveal_at`. The plaintext must match
+    // the hash committed in `add_puzzle` and can only be sealed once, before the reveal time.
+    // NOTE: contract state is public, so this only keeps the plaintext out of the views. Anyone reading
+    // the raw state can see it, don't seal a solution that must stay secret until then.
+    pub fn seal_solution(&mut self, puzzle_id: String, plaintext: String, reveal_at: U64) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert!(puzzle.sealed_plaintext.is_none(), "Solution is already sealed");
+        assert!(env::block_timestamp() < reveal_at.0, "Reveal time must be in the future");
+        assert_eq!(self.hash_answer(&plaintext, &puzzle.normalize_rules), puzzle.solution_hash, "Plaintext doesn't match the committed hash");
+        puzzle.reveal_at = reveal_at.0;
+        puzzle.sealed_plaintext = Some(plaintext);
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function. The sealed solution, once its reveal time has passed.
+    pub fn reveal_after_deadline(&self, puzzle_id: String) -> String {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let plaintext = puzzle.sealed_plaintext.expect("No sealed solution for this puzzle");
+        assert!(env::block_timestamp() >= puzzle.reveal_at, "Solution can't be revealed yet");
+        plaintext
+    }
+
+    // Immutable function. For countdowns: nanoseconds until `reveal_after_deadline` works, 0 once it does.
+    pub fn reveal_in_ns(&self, puzzle_id: String) -> u64 {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        assert!(puzzle.sealed_plaintext.is_some(), "No sealed solution for this puzzle");
+        puzzle.reveal_at.saturating_sub(env::block_timestamp())
+    }
+
+    // Mutable function, owner only. Restricts guessing to holders of at least one token from `nft_contract`
+    // (any NEP-171 contract implementing `nft_tokens_for_owner`). `None` lifts the restriction. While set, the
+    // puzzle can only be won with `guess_solution`: `guess_clue` refuses it.
+    pub fn set_required_nft(&mut self, puzzle_id: String, nft_contract: Option<AccountId>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.required_nft = nft_contract;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Callback, only this contract can call it. Receives the NFT contract's answer to `nft_tokens_for_owner`
+    // and finishes the guess only if the player holds at least one token. Otherwise the guess fee is refunded.
+    // If the puzzle was cancelled while waiting, the guess is dropped: `cancel_puzzle` already refunded the fee.
+    #[private]
+    pub fn resolve_gated_guess(&mut self, player: AccountId, puzzle_id: String, solution: String, fee: U128) -> bool {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        if puzzle.cancelled {
+            env::log_str(&format!("Puzzle {} was cancelled, the guess of {} is dropped", puzzle_id, player));
+            return false;
+        }
+        let holds_token = match env::promise_result(0) {
+            PromiseResult::Successful(value) => near_sdk::serde_json::from_slice::<Vec<near_sdk::serde_json::Value>>(&value)
+                .map(|tokens| !tokens.is_empty())
+                .unwrap_or(false),
+            _ => false,
+        };
+        if !holds_token {
+            env::log_str(&format!("{} doesn't hold the NFT required for puzzle {}", player, puzzle_id));
+            if fee.0 > 0 {
+                let fees_paid = puzzle.participants.get(&player).unwrap_or(0);
+                let fees_left = fees_paid.checked_sub(fee.0).expect("Fee was already refunded");
+                puzzle.participants.insert(&player, &fees_left);
+                self.puzzles.insert(&puzzle_id, &puzzle);
+                self.collected_fees = self.collected_fees.checked_sub(fee.0).expect("Fee was already refunded");
+                Promise::new(player).transfer(fee.0);
+            }
+            return false;
+        }
+        self.check_solution(&player, Some(&puzzle_id), &solution)
+    }
+
+    // Mutable function, owner only. Withdraws a flawed, unsolved puzzle and refunds every participant's guess fees.
+    // One transfer per participant, so gas grows with the number of participants.
+    pub fn cancel_puzzle(&mut self, puzzle_id: String) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert!(!puzzle.cancelled, "Puzzle is already cancelled");
+        assert!(puzzle.solve_feed.is_empty(), "Can't cancel a solved puzzle");
+        for (participant, fees_paid) in puzzle.participants.iter() {
+            if fees_paid > 0 {
+                Promise::new(participant).transfer(fees_paid);
+                self.collected_fees -= fees_paid;
+            }
+        }
+        puzzle.cancelled = true;
+        // an unused puzzle prize goes to the shared pool, or back to the owner if it's in tokens
+        match &puzzle.reward_token {
+            Some(reward_token) if puzzle.prize_pool > 0 => {
+                let args = json!({ "receiver_id": self.owner_id, "amount": U128(puzzle.prize_pool) }).to_string().into_bytes();
+                Promise::new(reward_token.clone()).function_call("ft_transfer".to_string(), args, 1, Gas(GAS_FOR_FT_TRANSFER));
+            }
+            Some(_) => {}
+            None => self.prize_pool += puzzle.prize_pool,
+        }
+        puzzle.prize_pool = 0;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        env::log_str(&format!("Puzzle {} was cancelled", puzzle_id));
+        self.sync_status(&puzzle_id);
+    }
+
+    // Mutable function, owner only. For good-cause events: `reclaim_pool` sends expired puzzles' pools to
+    // this account instead of the owner. `None` sends them back to the owner.
+    pub fn set_charity_account(&mut self, charity_account: Option<AccountId>) {
+        self.assert_owner();
+        self.charity_account = charity_account;
+    }
+
+    // Mutable function, owner only. Takes the own pool of a puzzle which expired unsolved out of the contract,
+    // to the charity account if one is set, otherwise to the owner.
+    pub fn reclaim_pool(&mut self, puzzle_id: String) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert_eq!(puzzle.status(), PuzzleStatus::Expired, "Only an expired puzzle's pool can be reclaimed");
+        assert!(puzzle.prize_pool > 0, "Puzzle has no pool");
+        let receiver_id = self.charity_account.clone().unwrap_or_else(|| self.owner_id.clone());
+        match &puzzle.reward_token {
+            Some(reward_token) => {
+                let args = json!({ "receiver_id": receiver_id, "amount": U128(puzzle.prize_pool) }).to_string().into_bytes();
+                Promise::new(reward_token.clone()).function_call("ft_transfer".to_string(), args, 1, Gas(GAS_FOR_FT_TRANSFER));
+            }
+            None => {
+                Promise::new(receiver_id.clone()).transfer(puzzle.prize_pool);
+            }
+        }
+        env::log_str(&format!("Pool of {} for puzzle {} sent to {}", puzzle.prize_pool, puzzle_id, receiver_id));
+        puzzle.prize_pool = 0;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Immutable function. For an activity feed: (solver, timestamp) pairs in solve order, paginated.
+    pub fn solve_feed(&self, puzzle_id: String, from_index: u64, limit: u64) -> Vec<(AccountId, u64)> {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        (from_index..puzzle.solve_feed.len())
+            .take(limit as usize)
+            .filter_map(|index| puzzle.solve_feed.get(index))
+            .collect()
+    }
+
+    // Immutable function. For a collection gallery: ids of the prize NFTs minted for the puzzle, paginated.
+    pub fn puzzle_tokens(&self, puzzle_id: String, from_index: u64, limit: u64) -> Vec<TokenId> {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        (from_index..puzzle.tokens.len())
+            .take(limit as usize)
+            .filter_map(|index| puzzle.tokens.get(index))
+            .collect()
+    }
+
+    // Immutable function. For archiving: every solver of the puzzle in solve order, Borsh serialized
+    // (a Vec<AccountId>) in a single blob instead of many `solve_feed` pages.
+    pub fn export_leaderboard(&self, puzzle_id: String) -> Base64VecU8 {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let solvers: Vec<AccountId> = puzzle.solve_feed.iter().map(|(solver, _)| solver).collect();
+        Base64VecU8(solvers.try_to_vec().expect("Failed to serialize the leaderboard"))
+    }
+
+    // Immutable function, owner only (so it must be called as a transaction). (guess length, number of guesses)
+    // buckets for tuning difficulty. Only lengths are tracked, never what was guessed.
+    pub fn guess_length_stats(&self, puzzle_id: String) -> Vec<(usize, u32)> {
+        self.assert_owner();
+        self.get_puzzle(&puzzle_id).guess_lengths.into_iter().collect()
+    }
+
+    // Immutable function. (solvers, unique participants) of a puzzle, for showing a solve percentage.
+    pub fn participation_rate(&self, puzzle_id: String) -> (u64, u64) {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        (puzzle.solve_feed.len(), puzzle.participants.len())
+    }
+
+    // Immutable function. 1-based position of the account in the puzzle's solve order, None if it hasn't solved.
+    pub fn rank_of(&self, puzzle_id: String, account_id: AccountId) -> Option<u64> {
+        assert!(self.solver_salt.is_none(), "Solvers are anonymous, use prove_rank");
+        self.puzzle_solves.get(&(puzzle_id, account_id)).map(|index| index + 1)
+    }
+
+    // Immutable function, but it must be called as a transaction: `rank_of` for the caller, which also works
+    // with anonymous solvers since only the caller can match their own hash.
+    pub fn prove_rank(&self, puzzle_id: String) -> Option<u64> {
+        let solver = self.solver_id(&env::predecessor_account_id());
+        self.puzzle_solves.get(&(puzzle_id, solver)).map(|index| index + 1)
+    }
+
+    // Immutable function. Number of accounts which solved the puzzle, available with anonymous solvers too.
+    pub fn solver_count(&self, puzzle_id: String) -> u64 {
+        self.get_puzzle(&puzzle_id).solve_feed.len()
+    }
+
+    // Mutable function, owner only. With anonymous solvers the solve feeds (and so `solve_feed`,
+    // `export_leaderboard` and `state_fingerprint`) hold hex sha256(salt + account) instead of accounts.
+    // Transactions stay public on chain, so this only keeps the leaderboards from listing who solved.
+    // NOTE: this is obfuscation only. The contract needs the salt to hash every new solver, so it stays in
+    // contract state, and anyone reading the raw state can hash known account ids with it to find them in a feed.
+    // Can only be switched before the first puzzle solve, so a feed never mixes accounts and hashes.
+    pub fn set_anonymous_solvers(&mut self, anonymous_solvers: bool) {
+        self.assert_owner();
+        assert!(
+            self.puzzles.values().all(|puzzle| puzzle.solve_feed.is_empty()),
+            "Can't change solver anonymity once puzzles have been solved"
+        );
+        self.solver_salt = if anonymous_solvers { Some(env::random_seed()) } else { None };
+    }
+
+    // Immutable function. Iterates over every puzzle, so gas grows with the size of the catalog
+    // (fine for a few hundred puzzles, use an indexer beyond that).
+    pub fn active_puzzle_count(&self) -> u64 {
+        self.puzzles
+            .values()
+            .filter(|puzzle| puzzle.status() == PuzzleStatus::Active)
+            .count() as u64
+    }
+
+    // Immutable function. For an "up next" box: (start timestamp, puzzle id) of the puzzle opening soonest,
+    // None if nothing is scheduled. Walks the whole catalog too.
+    pub fn next_puzzle(&self) -> Option<(u64, String)> {
+        let now = env::block_timestamp();
+        self.puzzles
+            .iter()
+            .filter(|(_, puzzle)| !puzzle.cancelled)
+            .filter_map(|(puzzle_id, puzzle)| puzzle.start_timestamp.map(|start| (start, puzzle_id)))
+            .filter(|(start, _)| *start > now)
+            .min()
+    }
+
+    // Immutable function. (solves summed over all puzzles, number of puzzles) for a global dashboard.
+    // Like `active_puzzle_count` it walks the whole catalog.
+    pub fn global_solve_rate(&self) -> (u64, u64) {
+        let solvers = self.puzzles.values().map(|puzzle| puzzle.solve_feed.len()).sum();
+        (solvers, self.puzzles.len())
+    }
+
+    // Immutable function. (earliest, latest) solve timestamp over all puzzles, for a "first ever solve" badge.
+    // None before any puzzle is solved. Only reads the ends of each solve feed, but walks the whole catalog.
+    pub fn global_solve_window(&self) -> Option<(u64, u64)> {
+        self.puzzles
+            .values()
+            .filter_map(|puzzle| {
+                let first = puzzle.solve_feed.get(0)?.1;
+                let last = puzzle.solve_feed.get(puzzle.solve_feed.len() - 1)?.1;
+                Some((first, last))
+            })
+            .reduce(|(earliest, latest), (first, last)| (earliest.min(first), latest.max(last)))
+    }
+
+    // Immutable function. Bundles meta, status and reward so frontends need a single view call.
+    pub fn puzzle_view(&self, puzzle_id: String) -> Option<PuzzleView> {
+        self.puzzles.get(&puzzle_id).map(|puzzle| {
+            let status = puzzle.status();
+            let linked_pool = puzzle.linked_puzzle.as_ref().map(|id| self.get_puzzle(id).prize_pool).unwrap_or(0);
+            let puzzle_pools = puzzle.prize_pool + linked_pool;
+            let pool = if puzzle_pools > 0 { puzzle_pools } else { self.prize_pool };
+            let potential_reward = if self.rewards_enabled && status == PuzzleStatus::Active && puzzle.reward_mode != RewardMode::None {
+                pool // with a random bonus, the first solver can get up to the whole pool
+            } else {
+                0
+            };
+            PuzzleView {
+                attempts_remaining: puzzle.attempts_remaining(),
+                meta: puzzle.meta,
+                status,
+                potential_reward: U128(potential_reward),
+            }
+        })
+    }
+
+    // Immutable function. The potential reward together with its currency, so frontends can show "10 USDC"
+    // or "5 NEAR". A token prize is the puzzle's whole token pool.
+    pub fn reward_info(&self, puzzle_id: String) -> RewardInfo {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let active = self.rewards_enabled && puzzle.status() == PuzzleStatus::Active;
+        match puzzle.reward_token {
+            Some(token) => RewardInfo { token: Some(token), amount: U128(if active { puzzle.prize_pool } else { 0 }) },
+            None => RewardInfo { token: None, amount: self.puzzle_view(puzzle_id).unwrap().potential_reward },
+        }
+    }
+
+    // Immutable function. `puzzle_view`'s potential reward as a NEAR amount, e.g. "1.5" for 1.5 NEAR.
+    pub fn potential_reward_human(&self, puzzle_id: String) -> String {
+        let view = self.puzzle_view(puzzle_id).expect("Puzzle not found");
+        format_near(view.potential_reward.0)
+    }
+
+    // Immutable function. For hosts cloning puzzles: one "field: a vs b" line per setting that differs between
+    // the two puzzles (answers and progress such as solvers aren't compared). Empty when they match.
+    pub fn diff_puzzles(&self, a: String, b: String) -> Vec<String> {
+        let (a, b) = (self.get_puzzle(&a), self.get_puzzle(&b));
+        let fields = [
+            ("title", format!("{:?}", a.meta.title), format!("{:?}", b.meta.title)),
+            ("callback_url", format!("{:?}", a.meta.callback_url), format!("{:?}", b.meta.callback_url)),
+            ("theme", format!("{:?}", a.meta.theme), format!("{:?}", b.meta.theme)),
+            ("clues", a.clues.len().to_string(), b.clues.len().to_string()),
+            ("grid_dimensions", format!("{:?}", a.grid_dimensions), format!("{:?}", b.grid_dimensions)),
+            ("start_timestamp", format!("{:?}", a.start_timestamp), format!("{:?}", b.start_timestamp)),
+            ("deadline", format!("{:?}", a.deadline), format!("{:?}", b.deadline)),
+            ("max_attempts", format!("{:?}", a.max_attempts), format!("{:?}", b.max_attempts)),
+            ("sudden_death", a.sudden_death.to_string(), b.sudden_death.to_string()),
+            ("required_nft", format!("{:?}", a.required_nft), format!("{:?}", b.required_nft)),
+            ("allowlist_only", a.allowlist_only.to_string(), b.allowlist_only.to_string()),
+            ("normalize_rules", format!("{:?}", a.normalize_rules), format!("{:?}", b.normalize_rules)),
+            ("prize_pool", a.prize_pool.to_string(), b.prize_pool.to_string()),
+            ("reward_token", format!("{:?}", a.reward_token), format!("{:?}", b.reward_token)),
+            ("reward_mode", format!("{:?}", a.reward_mode), format!("{:?}", b.reward_mode)),
+            ("reward_window_ns", a.reward_window_ns.to_string(), b.reward_window_ns.to_string()),
+            ("max_winners", format!("{:?}", a.max_winners), format!("{:?}", b.max_winners)),
+            ("linked_puzzle", format!("{:?}", a.linked_puzzle), format!("{:?}", b.linked_puzzle)),
+            ("soulbound_prizes", a.soulbound_prizes.to_string(), b.soulbound_prizes.to_string()),
+            ("practice_mode", a.practice_mode.to_string(), b.practice_mode.to_string()),
+            ("paused", a.paused.to_string(), b.paused.to_string()),
+        ];
+        fields
+            .iter()
+            .filter(|(_, value_a, value_b)| value_a != value_b)
+            .map(|(field, value_a, value_b)| format!("{}: {} vs {}", field, value_a, value_b))
+            .collect()
+    }
+
+    // Immutable function.
+    pub fn get_puzzle_meta(&self, puzzle_id: String) -> Option<PuzzleMeta> {
+        self.puzzles.get(&puzzle_id).map(|puzzle| puzzle.meta)
+    }
+
+    // Immutable function. The stored hex digest is safe to expose (it's a hash), and lets tooling verify a puzzle.
+    pub fn puzzle_solution_hash(&self, puzzle_id: String) -> Option<String> {
+        self.puzzles.get(&puzzle_id).map(|puzzle| puzzle.solution_hash)
+    }
+
+    // Immutable function. A mild hint which doesn't spoil the answer: how many characters it has.
+    pub fn answer_length_hint(&self, puzzle_id: String, clue: u16) -> u8 {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let clue = puzzle.clues.get(&clue).expect("Clue not found");
+        clue.length_hint.expect("No length hint for this clue")
+    }
+
+    // Mutable function requires a signed transaction. Checks the answer to a single clue and remembers it if right.
+    // Solving the last required clue solves the puzzle, so a clue guess is a guess like any other: it's payable
+    // and takes the same invite `code`, `password`, fee, cooldown and attempt limits as `guess_solution`.
+    // A wrong answer on a sudden death puzzle eliminates the account.
+    #[payable]
+    pub fn guess_clue(
+        &mut self,
+        puzzle_id: String,
+        clue_number: u16,
+        answer: String,
+        code: Option<String>,
+        password: Option<String>,
+    ) -> bool {
+        let player = env::predecessor_account_id();
+        let puzzle = self.get_puzzle(&puzzle_id);
+        // checking the NFT needs a cross-contract call, so gated puzzles only take `guess_solution`
+        assert!(puzzle.required_nft.is_none(), "Puzzle is NFT-gated, guess its solution instead");
+        let answer_hash = self.hash_answer(&answer, &puzzle.normalize_rules);
+        // tells a clue guess apart from the same answer to another clue or the solution, for the repeat check
+        let guess_hash = hex::encode(env::sha256(format!("{}:{}", clue_number, answer_hash).as_bytes()));
+        self.take_guess(&player, Some(&puzzle_id), code, password.as_deref(), guess_hash);
+        let clue = puzzle.clues.get(&clue_number).expect("Clue not found");
+        assert!(
+            self.is_clue_unlocked(player.clone(), puzzle_id.clone(), clue_number),
+            "Solve the prerequisite clues first"
+        );
+        if answer_hash == clue.answer_hash {
+            self.mark_clue_solved(&puzzle_id, &player, clue_number);
+            env::log_str("You guessed right!");
+            if self.is_fully_solved(player.clone(), puzzle_id.clone()) {
+                self.record_win(&player, Some(&puzzle_id)); // does nothing more if they had already solved it
+            }
+            true
+        } else if puzzle.sudden_death {
+            self.eliminated.insert(&(puzzle_id, player));
+            env::log_str("Wrong answer, you are out of this puzzle.");
+            false
+        } else {
+            let key = (puzzle_id.clone(), player.clone(), clue_number);
+            let failures = self.attempts_per_clue.get(&key).unwrap_or(0) + 1;
+            self.attempts_per_clue.insert(&key, &failures);
+            if self.accessibility_mode && failures >= self.auto_solve_after_failures {
+                self.mark_clue_solved(&puzzle_id, &player, clue_number);
+                self.assisted_players.insert(&(puzzle_id.clone(), player.clone()));
+                env::log_str(&format!("Clue {} was solved for you", clue_number));
+                if self.is_fully_solved(player.clone(), puzzle_id.clone()) {
+                    self.record_win(&player, Some(&puzzle_id));
+                }
+            } else {
+                env::log_str("Try again.");
+            }
+            false
+        }
+    }
+
+    // Mutable function requires a signed transaction. `guess_clue` followed by `score`, saving the frontend a
+    // round-trip: (whether the answer was right, the caller's score after it). The score is a percentage
+    // (0..=100), `score`'s basis points rounded down.
+    #[payable]
+    pub fn guess_clue_with_score(
+        &mut self,
+        puzzle_id: String,
+        clue_number: u16,
+        answer: String,
+        code: Option<String>,
+        password: Option<String>,
+    ) -> (bool, u8) {
+        let correct = self.guess_clue(puzzle_id.clone(), clue_number, answer, code, password);
+        (correct, (self.score(puzzle_id, env::predecessor_account_id()) / 100) as u8)
+    }
+
+    // Immutable function. Clue numbers the account hasn't solved yet, in ascending order.
+    pub fn unsolved_clues(&self, account_id: AccountId, puzzle_id: String) -> Vec<u16> {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        puzzle
+            .clues
+            .keys()
+            .copied()
+            .filter(|clue_number| !self.solved_clues.contains(&(puzzle_id.clone(), account_id.clone(), *clue_number)))
+            .collect()
+    }
+
+    // Immutable function. For difficulty heatmaps: (clue number, accounts that solved it) for every clue, in
+    // ascending clue order.
+    pub fn clue_solve_counts(&self, puzzle_id: String) -> Vec<(u16, u32)> {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        puzzle
+            .clues
+            .keys()
+            .map(|clue_number| (*clue_number, self.clue_solves.get(&(puzzle_id.clone(), *clue_number)).unwrap_or(0)))
+            .collect()
+    }
+
+    // Immutable function. Weight of the clues the account solved over the total weight, in basis points
+    // (10_000 = every clue). If all weights are 0 every clue counts the same.
+    pub fn score(&self, puzzle_id: String, account_id: AccountId) -> u32 {
+        let puzzle = self.get_puzzle(&puzzle_id);
+        let total_weight: u32 = puzzle.clues.values().map(|clue| clue.weight as u32).sum();
+        let weight_of = |clue: &Clue| if total_weight == 0 { 1 } else { clue.weight as u32 };
+        let total: u32 = puzzle.clues.values().map(weight_of).sum();
+        if total == 0 {
+            return 0; // no clues
+        }
+        let solved: u32 = puzzle
+            .clues
+            .iter()
+            .filter(|(clue_number, _)| self.solved_clues.contains(&(puzzle_id.clone(), account_id.clone(), **clue_number)))
+            .map(|(_, clue)| weight_of(clue))
+            .sum();
+        solved * 10_000 / total
+    }
+
+    // Immutable function. Clue numbers in a pseudo-random order derived from `seed`, so clue positions can't be
+    // memorised across rounds. The same seed always gives the same order; the answers are unaffected.
+    pub fn clue_order(&self, puzzle_id: String, seed: u64) -> Vec<u16> {
+        let mut order: Vec<u16> = self.get_puzzle(&puzzle_id).clues.keys().cloned().collect();
+        let mut state = seed;
+        // Fisher-Yates shuffle driven by a splitmix64 generator
+        for i in (1..order.len()).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        order
+    }
+
+    // Mutable function, owner or author only. Replaces the puzzle's meta, e.g. to fix a typo before freezing.
+    pub fn update_meta(&mut self, puzzle_id: String, meta: PuzzleMeta) {
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        self.assert_owner_or_author(&puzzle);
+        assert!(!puzzle.frozen, "Puzzle is frozen");
+        validate_meta(&meta);
+        puzzle.meta = meta;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. With `SplitAmongWindow` nobody is paid on solving: once `reward_window_ns`
+    // has passed since the first solve, `finalize_rewards` splits the pool between everyone who solved in time.
+    // Only for NEAR pools, and only before the puzzle is solved. `None` is for free community puzzles without a
+    // pool: no guess fee or stake is taken on them.
+    pub fn set_reward_mode(&mut self, puzzle_id: String, reward_mode: RewardMode, reward_window_ns: U64) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert!(puzzle.solve_feed.is_empty(), "Can't change the reward mode of a solved puzzle");
+        if reward_mode == RewardMode::SplitAmongWindow {
+            assert!(puzzle.reward_token.is_none(), "Split rewards are only supported for NEAR prizes");
+        }
+        if reward_mode == RewardMode::None {
+            assert_eq!(puzzle.prize_pool, 0, "Puzzle has a prize pool, cancel it to release the pool");
+            assert!(puzzle.reward_token.is_none(), "Puzzle pays a token prize");
+        }
+        puzzle.reward_mode = reward_mode;
+        puzzle.reward_window_ns = reward_window_ns.0;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function. Anyone can call it once the puzzle's reward window has closed: pays the pool out in
+    // equal shares to everyone credited within the window, the first solver also getting the rounding remainder
+    // (the usual caps and rollover apply). Accounts blacklisted since they solved are skipped. Rewards must be
+    // enabled, so the window solvers are never dropped unpaid.
+    pub fn finalize_rewards(&mut self, puzzle_id: String) {
+        assert!(self.rewards_enabled, "Rewards are disabled, enable them before finalizing");
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        assert_eq!(puzzle.reward_mode, RewardMode::SplitAmongWindow, "Puzzle doesn't split its rewards");
+        assert!(!puzzle.rewards_finalized, "Rewards were already paid");
+        let (_, first_solved_at) = puzzle.solve_feed.get(0).expect("Puzzle hasn't been solved yet");
+        assert!(
+            env::block_timestamp() > first_solved_at + puzzle.reward_window_ns,
+            "The reward window is still open"
+        );
+        puzzle.rewards_finalized = true;
+        let mut recipients = std::mem::take(&mut puzzle.window_solvers);
+        recipients.retain(|account_id| !self.blacklist.contains(account_id));
+        self.puzzles.insert(&puzzle_id, &puzzle);
+        if !recipients.is_empty() {
+            self.pay_reward(&recipients, false, Some(&puzzle_id), 0);
+        }
+    }
+
+    // Mutable function, owner only. For shared-link access: guesses on the puzzle (solution and clues) and letter
+    // purchases must come with the password whose hex sha256 is `password_hash`. `None` removes the password.
+    pub fn set_access_password(&mut self, puzzle_id: String, password_hash: Option<String>) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.access_password_hash = password_hash;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Restricts guessing on the puzzle to the accounts on its allowlist.
+    pub fn set_allowlist_only(&mut self, puzzle_id: String, allowlist_only: bool) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.allowlist_only = allowlist_only;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only.
+    pub fn add_to_allowlist(&mut self, puzzle_id: String, account_ids: Vec<AccountId>) {
+        self.assert_owner();
+        self.get_puzzle(&puzzle_id);
+        for account_id in account_ids {
+            self.allowlists.insert(&(puzzle_id.clone(), account_id));
+        }
+    }
+
+    // Immutable function. Ids of the puzzles the account can guess on right now: guessing isn't paused, the
+    // account isn't blacklisted, the puzzle is open and the account is on its allowlist (if it has one).
+    // NFT gates are checked by a cross-contract call during the guess, so NFT-gated puzzles are listed
+    // here even if the account holds no token (see `get_required_nft`). Password-protected puzzles are
+    // listed as well. Walks the whole catalog.
+    pub fn playable_puzzles(&self, account_id: AccountId) -> Vec<String> {
+        if self.is_paused() || self.blacklist.contains(&account_id) {
+            return vec![];
+        }
+        self.puzzles
+            .iter()
+            .filter(|(puzzle_id, puzzle)| {
+                puzzle.is_open()
+                    && self.is_allowed(puzzle_id, puzzle, &account_id)
+                    && !self.is_eliminated(account_id.clone(), puzzle_id.clone())
+            })
+            .map(|(puzzle_id, _)| puzzle_id)
+            .collect()
+    }
+
+    // Immutable function. The NFT contract a puzzle is gated by, if any.
+    pub fn get_required_nft(&self, puzzle_id: String) -> Option<AccountId> {
+        self.get_puzzle(&puzzle_id).required_nft
+    }
+
+    // Mutable function, owner only. Stops guesses on one puzzle, e.g. while a reported mistake is checked.
+    pub fn pause_puzzle(&mut self, puzzle_id: String) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.paused = true;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only.
+    pub fn unpause_puzzle(&mut self, puzzle_id: String) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.paused = false;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Freezing is one way: no more edits after this.
+    pub fn freeze_puzzle(&mut self, puzzle_id: String) {
+        self.assert_owner();
+        let mut puzzle = self.get_puzzle(&puzzle_id);
+        puzzle.frozen = true;
+        self.puzzles.insert(&puzzle_id, &puzzle);
+    }
+
+    // Mutable function, owner only. Switches the optional features on or off.
+    pub fn set_features(&mut self, rewards_enabled: bool, minting_enabled: bool, cross_contract_enabled: bool) {
+        self.assert_owner();
+        self.rewards_enabled = rewards_enabled;
+        self.minting_enabled = minting_enabled;
+        self.cross_contract_enabled = cross_contract_enabled;
+    }
+
+    // Immutable function. Informational only: a conservative amount of gas to attach to `guess_solution`
+    // given the features currently enabled (a winning guess does the most work).
+    pub fn estimated_guess_gas(&self) -> u64 {
+        let mut gas = GAS_FOR_GUESS;
+        if self.rewards_enabled {
+            gas += GAS_FOR_REWARD;
+        }
+        if self.minting_enabled {
+            gas += GAS_FOR_MINT;
+        }
+        if self.cross_contract_enabled {
+            gas += GAS_FOR_CROSS_CONTRACT;
+        }
+        gas
+    }
+    
+    // Immutable function. Average gas actually used by past guesses, 0 before the first one. Rough: callbacks
+    // (NFT gates) and the work after the sample aren't included. Compare with `estimated_guess_gas`.
+    pub fn avg_guess_gas(&self) -> u64 {
+        if self.gas_samples == 0 {
+            return 0;
+        }
+        (self.total_gas_logged / self.gas_samples as u128) as u64
+    }
+
+    // Immutable function. 
+    pub fn get_solution(&self) -> String {
+        self.crossword_solution.clone()
+    }
+
+    // Mutable function, owner only. Stops guessing. If `auto_unpause_at` (ns timestamp) is given the
+    // pause ends by itself at that time, so the contract can't stay stuck if the owner forgets.
+    pub fn pause(&mut self, auto_unpause_at: Option<U64>) {
+        self.assert_owner();
+        self.paused = true;
+        self.auto_unpause_at = auto_unpause_at.map(|t| t.0);
+    }
+
+    // Mutable function, owner only.
+    pub fn unpause(&mut self) {
+        self.assert_owner();
+        self.paused = false;
+        self.auto_unpause_at = None;
+    }
+
+    // Immutable function.
+    pub fn is_paused(&self) -> bool {
+        match self.auto_unpause_at {
+            Some(unpause_at) if env::block_timestamp() >= unpause_at => false,
+            _ => self.paused,
+        }
+    }
+
+    // Mutable function, owner only. Stops bots spamming one wrong guess: a guess whose normalized hash matches
+    // the caller's previous guess on the same puzzle is rejected.
+    pub fn set_reject_repeat_guesses(&mut self, reject_repeat_guesses: bool) {
+        self.assert_owner();
+        self.reject_repeat_guesses = reject_repeat_guesses;
+    }
+
+    // Mutable function, owner only. Tunes rate limiting without redeploying.
+    pub fn set_cooldown(&mut self, cooldown_ns: u64) {
+        self.assert_owner();
+        env::log_str(&format!("Cooldown changed from {} ns to {} ns", self.cooldown_ns, cooldown_ns));
+        self.cooldown_ns = cooldown_ns;
+    }
+
+    // Immutable function.
+    pub fn get_cooldown(&self) -> u64 {
+        self.cooldown_ns
+    }
+
+    // Mutable function, owner only. Rejects guesses (solution and clues) and letter purchases relayed by contracts
+    // (predecessor != signer), so bots can't play from a contract. It can't stop someone scripting transactions
+    // from a plain account.
+    pub fn set_humans_only(&mut self, humans_only: bool) {
+        self.assert_owner();
+        self.humans_only = humans_only;
+    }
+
+    // Mutable function, owner only. Blocks a cheating account from guessing and claiming prizes.
+    pub fn add_to_blacklist(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.blacklist.insert(&account_id);
+    }
+
+    // Mutable function, owner only.
+    pub fn remove_from_blacklist(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.blacklist.remove(&account_id);
+    }
+
+    // Mutable function, owner only. Turns the invite-only mode on or off.
+    pub fn set_require_invite(&mut self, require_invite: bool) {
+        self.assert_owner();
+        self.require_invite = require_invite;
+    }
+
+    // Mutable function, owner only. Stores hashes (not the codes themselves) so codes can't be read from state.
+    pub fn add_invite_codes(&mut self, code_hashes: Vec<String>) {
+        self.assert_owner();
+        for code_hash in code_hashes.iter() {
+            self.invite_codes.insert(code_hash);
+        }
+    }
+
+    // Mutable function. An account can found a new team itself or join one a member invited it to (see
+    // `invite_to_team`), or the owner can place it in one. Teammates share solve credit and split prizes equally,
+    // so nobody gets into a team without the consent of its members. At most MAX_TEAM_SIZE members.
+    pub fn join_team(&mut self, account_id: AccountId, team: String) {
+        let caller = env::predecessor_account_id();
+        assert!(caller == account_id || caller == self.owner_id, "Only the account itself or the owner can do this");
+        assert!(self.teams.get(&account_id).is_none(), "Account is already in a team");
+        let mut members = self.team_members.get(&team).unwrap_or_default();
+        assert!(members.len() < MAX_TEAM_SIZE, "Team is full, it can have at most {} members", MAX_TEAM_SIZE);
+        let invited = self.team_invites.remove(&(team.clone(), account_id.clone()));
+        assert!(
+            members.is_empty() || invited || caller == self.owner_id,
+            "Only accounts invited by a member can join this team"
+        );
+        members.push(account_id.clone());
+        self.team_members.insert(&team, &members);
+        self.teams.insert(&account_id, &team);
+    }
+
+    // Mutable function, team members (or the owner) only. Lets `account_id` join the team with `join_team`.
+    pub fn invite_to_team(&mut self, team: String, account_id: AccountId) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            self.teams.get(&caller).as_ref() == Some(&team) || caller == self.owner_id,
+            "Only members of the team can invite to it"
+        );
+        self.team_invites.insert(&(team, account_id));
+    }
+
+    // Mutable function. The caller leaves its team, it no longer shares the team's solve credit and prizes.
+    pub fn leave_team(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let team = self.teams.remove(&account_id).expect("Account is not in a team");
+        let mut members = self.team_members.get(&team).unwrap_or_default();
+        members.retain(|member| member != &account_id);
+        if members.is_empty() {
+            self.team_members.remove(&team);
+        } else {
+            self.team_members.insert(&team, &members);
+        }
+    }
+
+    // Immutable function.
+    pub fn get_team(&self, account_id: AccountId) -> Option<String> {
+        self.teams.get(&account_id)
+    }
+
+    // Immutable function. For leaderboards: whether each account has solved, in the same order as given.
+    pub fn have_solved(&self, account_ids: Vec<AccountId>) -> Vec<bool> {
+        assert!(account_ids.len() <= MAX_BATCH_QUERY, "Too many accounts, the limit is {}", MAX_BATCH_QUERY);
+        account_ids.iter().map(|account_id| self.solvers.contains(account_id)).collect()
+    }
+
+    // Immutable function. For streak rewards: number of consecutive UTC days, ending today or yesterday, on
+    // which the account guessed. 0 once a whole day has passed without a guess.
+    pub fn activity_streak(&self, account_id: AccountId) -> u32 {
+        match self.attempts.get(&account_id) {
+            Some(attempts) if env::block_timestamp() / DAY_NS <= attempts.last_guess_at / DAY_NS + 1 => attempts.streak,
+            _ => 0,
+        }
+    }
+
+    // Immutable function. (first guess, last guess) block timestamps for an account, None if it never guessed.
+    pub fn guess_window(&self, account_id: AccountId) -> Option<(u64, u64)> {
+        self.attempts
+            .get(&account_id)
+            .map(|attempts| (attempts.first_guess_at, attempts.last_guess_at))
+    }
+
+    // Mutable function, owner only. Privacy option for `get_attempts`.
+    pub fn set_attempts_public(&mut self, attempts_public: bool) {
+        self.assert_owner();
+        self.attempts_public = attempts_public;
+    }
+
+    // Mutable function, winners only. Attaches a note to the caller's solve (replacing any earlier one), readable
+    // by the caller through `get_my_note` and by the owner through `get_note`.
+    // NOTE: contract state is public, the views only keep the note out of sight. Encrypt it client-side (e.g.
+    // for the owner's and your own key) if it must stay private.
+    pub fn attach_note(&mut self, note: String) {
+        let account_id = env::predecessor_account_id();
+        assert!(self.solvers.contains(&account_id), "Only winners can attach a note");
+        assert!(note.len() <= MAX_NOTE_LEN, "Note is too long, the limit is {} bytes", MAX_NOTE_LEN);
+        self.notes.insert(&account_id, &note);
+    }
+
+    // Immutable function. The note the caller attached, so it only makes sense in a signed call.
+    pub fn get_my_note(&self) -> Option<String> {
+        self.notes.get(&env::predecessor_account_id())
+    }
+
+    // Immutable function, owner only.
+    pub fn get_note(&self, account_id: AccountId) -> Option<String> {
+        self.assert_owner();
+        self.notes.get(&account_id)
+    }
+
+    // Immutable function. Number of guesses an account has made, 0 if it never guessed.
+    pub fn get_attempts(&self, account_id: AccountId) -> u64 {
+        if !self.attempts_public {
+            assert_eq!(env::predecessor_account_id(), account_id, "Attempts are private");
+        }
+        self.attempts.get(&account_id).map(|attempts| attempts.count).unwrap_or(0)
+    }
+
+    // Immutable function. For migrating players between contract versions: the account's guessing history,
+    // solved clues and wrong answers per clue, as a Borsh serialized `PlayerProgress`. Private like
+    // `get_attempts` unless attempts are public. Walks every clue of every puzzle.
+    pub fn export_progress(&self, account_id: AccountId) -> Base64VecU8 {
+        if !self.attempts_public {
+            assert_eq!(env::predecessor_account_id(), account_id, "Attempts are private");
+        }
+        let mut progress = PlayerProgress {
+            attempts: self.attempts.get(&account_id),
+            solved_clues: vec![],
+            clue_failures: vec![],
+        };
+        for (puzzle_id, puzzle) in self.puzzles.iter() {
+            for clue_number in puzzle.clues.keys() {
+                let key = (puzzle_id.clone(), account_id.clone(), *clue_number);
+                if self.solved_clues.contains(&key) {
+                    progress.solved_clues.push((puzzle_id.clone(), *clue_number));
+                }
+                if let Some(failures) = self.attempts_per_clue.get(&key) {
+                    progress.clue_failures.push((puzzle_id.clone(), *clue_number, failures));
+                }
+            }
+        }
+        Base64VecU8(progress.try_to_vec().expect("Failed to serialize the progress"))
+    }
+
+    // Mutable function, owner only. Restores what `export_progress` returned (possibly on another deployment)
+    // for the account. The puzzles and clues it refers to must exist here.
+    pub fn import_progress(&mut self, account_id: AccountId, blob: Base64VecU8) {
+        self.assert_owner();
+        let progress = PlayerProgress::try_from_slice(&blob.0).expect("Invalid progress blob");
+        if let Some(attempts) = progress.attempts {
+            self.attempts.insert(&account_id, &attempts);
+        }
+        for (puzzle_id, clue_number) in progress.solved_clues {
+            assert!(self.get_puzzle(&puzzle_id).clues.contains_key(&clue_number), "Clue not found");
+            self.mark_clue_solved(&puzzle_id, &account_id, clue_number);
+        }
+        for (puzzle_id, clue_number, failures) in progress.clue_failures {
+            assert!(self.get_puzzle(&puzzle_id).clues.contains_key(&clue_number), "Clue not found");
+            self.attempts_per_clue.insert(&(puzzle_id, account_id.clone(), clue_number), &failures);
+        }
+    }
+
+    // Mutable function, owner only. Public key the host signs attestations with, `None` turns attestations off.
+    pub fn set_attestation_key(&mut self, public_key: Option<Vec<u8>>) {
+        self.assert_owner();
+        if let Some(public_key) = &public_key {
+            assert_eq!(public_key.len(), 32, "Expected a 32 byte ed25519 public key");
+        }
+        self.attestation_key = public_key;
+    }
+
+    // Mutable function requires a signed transaction. For a hybrid setup: the host checks the answer off-chain
+    // and signs a JSON `Attestation` for the player, who submits it here. No hashes have to be stored.
+    // NOTE: our near-sdk version has no `env::ed25519_verify`, so the signature is checked in wasm by the
+    // `ed25519-dalek` crate. That costs much more gas and needs the `ed25519-dalek` feature.
+    // The same access rules as `guess_solution` apply, so `password` is needed for password-protected puzzles.
+    // NFT gates are the exception (they need a cross-contract call): the host should check those before signing.
+    pub fn guess_with_attestation(&mut self, attestation: Vec<u8>, signature: Vec<u8>, password: Option<String>) -> bool {
+        let player = env::predecessor_account_id();
+        let public_key = self.attestation_key.as_ref().expect("Attestations are not enabled");
+        assert!(verify_ed25519(public_key, &attestation, &signature), "Invalid attestation signature");
+        let attestation: Attestation =
+            near_sdk::serde_json::from_slice(&attestation).expect("Attestation is not valid JSON");
+        assert_eq!(attestation.account_id, player, "Attestation is for another account");
+        self.assert_can_guess(&player, attestation.puzzle_id.as_deref(), password.as_deref());
+        self.record_attempt(&player);
+        self.record_win(&player, attestation.puzzle_id.as_deref());
+        true
+    }
+
+    // Mutable function requires a signed transaction. Now fn returns a bool type (not String)!
+    // Payable so that the guess fee (if one is set) can be attached.
+    // `code` is a single-use invite code, only needed when invites are required.
+    // `puzzle_id` picks a puzzle from the catalog; without it the guess is for the crossword set in `new`.
+    // `password` is only needed for puzzles with an access password.
+    // For NFT-gated puzzles the result comes back through a promise, otherwise straight away.
+    #[payable]
+    pub fn guess_solution(
+        &mut self,
+        solution: String,
+        code: Option<String>,
+        puzzle_id: Option<String>,
+        password: Option<String>,
+    ) -> PromiseOrValue<bool> {
+        let player = env::predecessor_account_id();
+        let rules = match &puzzle_id {
+            Some(puzzle_id) => self.get_puzzle(puzzle_id).normalize_rules,
+            None => self.normalize_rules,
+        };
+        let guess_hash = self.hash_answer(&solution, &rules);
+        let kept_fee = self.take_guess(&player, puzzle_id.as_deref(), code, password.as_deref(), guess_hash);
+        let required_nft = puzzle_id.as_ref().and_then(|puzzle_id| self.get_puzzle(puzzle_id).required_nft);
+        if let Some(puzzle_id) = &puzzle_id {
+            self.record_guess_length(puzzle_id, &solution);
+        }
+
+        let result = match (required_nft, puzzle_id) {
+            // Ask the NFT contract whether the player holds a token, then finish in `resolve_gated_guess`
+            (Some(nft_contract), Some(puzzle_id)) => {
+                let args = json!({ "account_id": player, "limit": 1 }).to_string().into_bytes();
+                let callback_args = json!({
+                    "player": player,
+                    "puzzle_id": puzzle_id,
+                    "solution": solution,
+                    "fee": U128(kept_fee),
+                })
+                .to_string()
+                .into_bytes();
+                Promise::new(nft_contract)
+                    .function_call("nft_tokens_for_owner".to_string(), args, 0, Gas(GAS_FOR_NFT_CHECK))
+                    .then(Promise::new(env::current_account_id()).function_call(
+                        "resolve_gated_guess".to_string(),
+                        callback_args,
+                        0,
+                        Gas(GAS_FOR_RESOLVE_GATED_GUESS),
+                    ))
+                    .into()
+            }
+            (_, puzzle_id) => PromiseOrValue::Value(self.check_solution(&player, puzzle_id.as_deref(), &solution)),
+        };
+        self.total_gas_logged += env::used_gas().0 as u128;
+        self.gas_samples += 1;
+        result
+    }
+}
+
+impl Puzzle {
+    fn status(&self) -> PuzzleStatus {
+        if self.cancelled {
+            return PuzzleStatus::Cancelled;
+        }
+        if !self.solve_feed.is_empty() {
+            return PuzzleStatus::Solved;
+        }
+        let now = env::block_timestamp();
+        if matches!(self.start_timestamp, Some(start) if now < start) {
+            return PuzzleStatus::NotStarted;
+        }
+        match self.deadline {
+            Some(deadline) if now >= deadline => PuzzleStatus::Expired,
+            _ => PuzzleStatus::Active,
+        }
+    }
+
+    // Whether the puzzle currently takes guesses, `assert_open` without the panic.
+    fn is_open(&self) -> bool {
+        let now = env::block_timestamp();
+        !self.cancelled
+            && !self.paused
+            && !matches!(self.start_timestamp, Some(start) if now < start)
+            && !matches!(self.deadline, Some(deadline) if now >= deadline)
+            && self.attempts_remaining() != Some(0)
+    }
+
+    fn attempts_remaining(&self) -> Option<u64> {
+        self.max_attempts.map(|max| max.saturating_sub(self.guess_count))
+    }
+
+    // Panics unless the puzzle currently takes guesses (solved puzzles still do, for the solve feed).
+    fn assert_open(&self) {
+        assert!(!self.cancelled, "Puzzle was cancelled");
+        assert!(!self.paused, "Puzzle is paused");
+        let now = env::block_timestamp();
+        if let Some(start) = self.start_timestamp {
+            assert!(now >= start, "Puzzle not started");
+        }
+        if let Some(deadline) = self.deadline {
+            assert!(now < deadline, "Puzzle has expired");
+        }
+        assert!(self.attempts_remaining() != Some(0), "Puzzle has no attempts left");
+    }
+}
+
+// Helper methods which are not exposed to the blockchain (no #[near_bindgen] on this impl).
+impl Contract {
+    // Everything a guess goes through before its answer is compared, whether it's for the solution or a clue:
+    // the access rules, gas for a token reward, invite code, guess fee and stake, cooldown, repeat check and
+    // the attempt counters. `guess_hash` identifies the guess for the repeat check. Returns the fee the
+    // contract kept (0 if it was burned), which is refunded if the puzzle is cancelled.
+    fn take_guess(
+        &mut self,
+        player: &AccountId,
+        puzzle_id: Option<&str>,
+        code: Option<String>,
+        password: Option<&str>,
+        guess_hash: String,
+    ) -> Balance {
+        self.assert_can_guess(player, puzzle_id, password);
+        let mut no_reward = false;
+        if let Some(puzzle_id) = puzzle_id {
+            let puzzle = self.get_puzzle(puzzle_id);
+            if self.rewards_enabled && puzzle.reward_token.is_some() {
+                require!(
+                    env::prepaid_gas() >= Gas(MIN_GAS_FOR_REWARD),
+                    format!(
+                        "Not enough gas attached to pay a token reward, attach at least {} TGas",
+                        MIN_GAS_FOR_REWARD / TGAS
+                    )
+                );
+            }
+            no_reward = puzzle.reward_mode == RewardMode::None;
+            self.emit_countdown(puzzle_id);
+        }
+        if self.require_invite {
+            let code = code.expect("An invite code is required");
+            let code_hash = hex::encode(env::sha256(code.as_bytes()));
+            // removing the code consumes it, so each code works only once
+            assert!(self.invite_codes.remove(&code_hash), "Invalid or already used invite code");
+        }
+        // puzzles without a reward are free to play
+        let (guess_fee, min_stake_to_play) = if no_reward { (0, 0) } else { (self.guess_fee, self.min_stake_to_play) };
+        let deposit = env::attached_deposit();
+        assert!(deposit >= guess_fee, "Attach at least the guess fee");
+        assert!(deposit >= min_stake_to_play, "Attach at least the minimum stake to play");
+        // fee the contract keeps, and may refund later
+        let kept_fee = if self.burn_fees {
+            if guess_fee > 0 {
+                Promise::new(AccountId::new_unchecked(BURN_ACCOUNT.to_string())).transfer(guess_fee);
+            }
+            0
+        } else {
+            guess_fee
+        };
+        self.collected_fees += kept_fee;
+        let refund = deposit - guess_fee;
+        if refund > 0 {
+            Promise::new(player.clone()).transfer(refund); // stake is only proof of funds, give it back
+        }
+        if let Some(attempts) = self.attempts.get(player) {
+            assert!(
+                env::block_timestamp() >= attempts.last_guess_at + self.cooldown_ns,
+                "Please wait for the cooldown before guessing again"
+            );
+        }
+        if self.reject_repeat_guesses {
+            let last_guess = (puzzle_id.map(str::to_string), guess_hash);
+            assert!(self.last_guesses.get(player).as_ref() != Some(&last_guess), "Same guess as last time");
+            self.last_guesses.insert(player, &last_guess);
+        }
+        self.record_attempt(player);
+        if let Some(puzzle_id) = puzzle_id {
+            self.record_guess(puzzle_id, player, kept_fee);
+        }
+        kept_fee
+    }
+
+    // Compares a (fee-paid, rate-limited) guess with the stored hash and handles a win.
+    fn check_solution(&mut self, player: &AccountId, puzzle_id: Option<&str>, solution: &str) -> bool {
+        let (expected_hash, rules) = match puzzle_id {
+            Some(puzzle_id) => {
+                let puzzle = self.get_puzzle(puzzle_id);
+                (puzzle.solution_hash, puzzle.normalize_rules)
+            }
+            None => (self.crossword_solution.clone(), self.normalize_rules),
+        };
+        let hashed_input_hex = self.hash_answer(solution, &rules);
+
+        if hashed_input_hex == expected_hash {
+            self.record_win(player, puzzle_id);
+            true
+        } else {
+            match puzzle_id {
+                Some(puzzle_id) if self.get_puzzle(puzzle_id).sudden_death => {
+                    self.eliminated.insert(&(puzzle_id.to_string(), player.clone()));
+                    env::log_str("Wrong guess, you are out of this puzzle.");
+                }
+                _ => env::log_str("Try again."),
+            }
+            false
+        }
+    }
+
+    // Credits a correct answer: solve feed, team credit, reward and prize NFT.
+    fn record_win(&mut self, player: &AccountId, puzzle_id: Option<&str>) {
+        if let Some(puzzle_id) = puzzle_id {
+            if self.get_puzzle(puzzle_id).practice_mode {
+                env::log_str("You guessed right! (practice, no rewards)");
+                return;
+            }
+        }
+        let winner = player.clone();
+        // false if this account had solved (this puzzle) before
+        let (first_solve, first_solver) = match puzzle_id {
+            Some(puzzle_id) => {
+                let index = self.record_solve(puzzle_id, &winner);
+                (index.is_some(), index == Some(0))
+            }
+            None => (!self.solvers.contains(&winner), true), // the crossword pays its whole pool to one winner
+        };
+        env::log_str("You guessed right!");
+        // a team member's solve counts for the whole team
+        let credited = self.credited_accounts(&winner);
+        for member in credited.iter() {
+            self.solvers.insert(member);
+        }
+        // players who had clues auto-solved (accessible mode) and solvers of puzzles without a reward get no
+        // reward or prize
+        let assisted = matches!(puzzle_id, Some(id) if self.assisted_players.contains(&(id.to_string(), winner.clone())));
+        let no_reward = matches!(puzzle_id, Some(id) if self.get_puzzle(id).reward_mode == RewardMode::None);
+        let first_solve = first_solve && !assisted && !no_reward;
+        // split puzzles pay everyone at once in `finalize_rewards`
+        let split_puzzle = puzzle_id.filter(|id| self.get_puzzle(id).reward_mode == RewardMode::SplitAmongWindow);
+        if let Some(puzzle_id) = split_puzzle {
+            if first_solve {
+                self.add_window_solvers(puzzle_id, &credited);
+            }
+        } else if first_solve && self.rewards_enabled && self.take_reward_slot(puzzle_id) {
+            let reward_token = puzzle_id.and_then(|puzzle_id| self.get_puzzle(puzzle_id).reward_token);
+            match (puzzle_id, reward_token) {
+                (Some(puzzle_id), Some(reward_token)) => self.pay_token_reward(&credited, puzzle_id, reward_token),
+                _ => {
+                    let withheld = puzzle_id.map(|puzzle_id| self.withheld_bonuses(puzzle_id, &winner)).unwrap_or(0);
+                    self.pay_reward(&credited, first_solver, puzzle_id, withheld)
+                }
+            }
+        }
+        if first_solve && self.minting_enabled {
+            self.mint_prize(winner, puzzle_id);
+        }
+    }
+
+    // Normalizes a guess and returns its hex encoded hash.
+    fn hash_answer(&self, answer: &str, rules: &NormalizeRules) -> String {
+        let normalized = normalize(answer, rules);
+        let hash = match self.hash_algo {
+            HashAlgo::Sha256 => env::sha256(normalized.as_bytes()),
+            HashAlgo::Keccak256 => env::keccak256(normalized.as_bytes()),
+            HashAlgo::Blake2b => blake2b(normalized.as_bytes()),
+        };
+        hex::encode(hash)
+    }
+
+    fn assert_owner(&self) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only the owner can call this method");
+    }
+
+    // Panics unless the account may play the puzzle right now: guessing isn't paused, the call isn't relayed
+    // by a contract (with `humans_only`), the account isn't blacklisted, allowlisted if needed and not
+    // eliminated, and the puzzle is open.
+    fn assert_can_play(&self, puzzle_id: &str, puzzle: &Puzzle, account_id: &AccountId) {
+        assert!(!self.is_paused(), "Guessing is paused");
+        self.assert_human(account_id);
+        self.assert_not_blacklisted(account_id);
+        puzzle.assert_open();
+        assert!(self.is_allowed(puzzle_id, puzzle, account_id), "Account is not on the allowlist");
+        assert!(!self.is_eliminated(account_id.clone(), puzzle_id.to_string()), "Eliminated from this puzzle");
+    }
+
+    // Access rules every guess goes through, however it's submitted, and buying letters too: `assert_can_play`
+    // for a puzzle (plus its password), only the pause, `humans_only` and blacklist for the crossword.
+    fn assert_can_guess(&self, player: &AccountId, puzzle_id: Option<&str>, password: Option<&str>) {
+        match puzzle_id {
+            Some(puzzle_id) => {
+                let puzzle = self.get_puzzle(puzzle_id);
+                self.assert_can_play(puzzle_id, &puzzle, player);
+                if let Some(password_hash) = &puzzle.access_password_hash {
+                    let password = password.expect("This puzzle needs a password");
+                    assert_eq!(&hex::encode(env::sha256(password.as_bytes())), password_hash, "Wrong password");
+                }
+            }
+            None => {
+                assert!(!self.is_paused(), "Guessing is paused");
+                self.assert_human(player);
+                self.assert_not_blacklisted(player);
+            }
+        }
+    }
+
+    // With `humans_only`, panics if the call was relayed by a contract instead of coming from the signer.
+    fn assert_human(&self, account_id: &AccountId) {
+        if self.humans_only {
+            assert_eq!(account_id, &env::signer_account_id(), "Guesses must be signed by the calling account");
+        }
+    }
+
+    fn assert_owner_or_author(&self, puzzle: &Puzzle) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner_id || caller == puzzle.author,
+            "Only the owner or the puzzle's author can call this method"
+        );
+    }
+
+    // Moves the puzzle, with its place in the author stats, to `author`.
+    fn change_author(&mut self, puzzle_id: &str, author: AccountId) {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        self.update_author_stats(&puzzle.author, |stats| stats.puzzles_created -= 1);
+        self.update_author_stats(&author, |stats| stats.puzzles_created += 1);
+        puzzle.author = author;
+        self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+    }
+
+    fn is_allowed(&self, puzzle_id: &str, puzzle: &Puzzle, account_id: &AccountId) -> bool {
+        !puzzle.allowlist_only || self.allowlists.contains(&(puzzle_id.to_string(), account_id.clone()))
+    }
+
+    // How the account appears in solve feeds: itself, or its salted hash with anonymous solvers. The hex hash is
+    // 64 characters long, so it's a valid (implicit) account id and fits the same storage.
+    fn solver_id(&self, account_id: &AccountId) -> AccountId {
+        match &self.solver_salt {
+            Some(salt) => {
+                let mut salted = salt.clone();
+                salted.extend_from_slice(account_id.as_bytes());
+                AccountId::new_unchecked(hex::encode(env::sha256(&salted)))
+            }
+            None => account_id.clone(),
+        }
+    }
+
+    // Panics unless `hash` is a lowercase hex digest of the length the current hash algorithm produces.
+    fn assert_valid_hash(&self, hash: &str) {
+        let hex_len = match self.hash_algo {
+            HashAlgo::Sha256 | HashAlgo::Keccak256 => 64,
+            HashAlgo::Blake2b => 128,
+        };
+        assert!(
+            hash.len() == hex_len && hash.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+            "Invalid hash {}, expected {} lowercase hex characters",
+            hash,
+            hex_len
+        );
+    }
+
+    fn assert_not_blacklisted(&self, account_id: &AccountId) {
+        assert!(!self.blacklist.contains(account_id), "Account is blacklisted");
+    }
+
+    // Remembers that `account_id` guessed on the puzzle and how much it paid in fees (for refunds).
+    fn record_guess(&mut self, puzzle_id: &str, account_id: &AccountId, fee: Balance) {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        let fees_paid = puzzle.participants.get(account_id).unwrap_or(0);
+        puzzle.participants.insert(account_id, &(fees_paid + fee));
+        puzzle.guess_count += 1;
+        self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+    }
+
+    // Counts how long a guess at the puzzle's solution was, for difficulty stats.
+    fn record_guess_length(&mut self, puzzle_id: &str, guess: &str) {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        *puzzle.guess_lengths.entry(guess.chars().count()).or_insert(0) += 1;
+        self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+    }
+
+    // Adds the solver to the puzzle's solve feed and returns their index in it (0 = first solver).
+    // Returns None (and records nothing) if they had already solved it.
+    fn record_solve(&mut self, puzzle_id: &str, solver: &AccountId) -> Option<u64> {
+        let solver = &self.solver_id(solver);
+        let key = (puzzle_id.to_string(), solver.clone());
+        if self.puzzle_solves.get(&key).is_some() {
+            return None;
+        }
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        let index = puzzle.solve_feed.len();
+        self.puzzle_solves.insert(&key, &index);
+        puzzle.solve_feed.push(&(solver.clone(), env::block_timestamp()));
+        self.puzzles.insert(&key.0, &puzzle);
+        self.update_author_stats(&puzzle.author, |stats| stats.total_solvers += 1);
+        self.sync_status(puzzle_id);
+        Some(index)
+    }
+
+    fn update_author_stats(&mut self, author: &AccountId, update: impl FnOnce(&mut AuthorStats)) {
+        let mut stats = self.author_stats(author.clone());
+        update(&mut stats);
+        self.author_stats.insert(author, &stats);
+    }
+
+    // Emits a NEP-297 event if the puzzle's status changed since the last event and remembers the new status.
+    fn sync_status(&mut self, puzzle_id: &str) -> PuzzleStatus {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        let status = puzzle.status();
+        if status != puzzle.last_status {
+            log_event(
+                "puzzle_status_changed",
+                json!({ "puzzle_id": puzzle_id, "old_status": puzzle.last_status, "new_status": status }),
+            );
+            puzzle.last_status = status;
+            self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+        }
+        status
+    }
+
+    // Contracts can't run timers, so a guess which notices that a countdown milestone was crossed since
+    // the last event emits it. If several were crossed in the meantime only the closest one is emitted.
+    fn emit_countdown(&mut self, puzzle_id: &str) {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        let deadline = match puzzle.deadline {
+            Some(deadline) => deadline,
+            None => return,
+        };
+        let time_left = deadline.saturating_sub(env::block_timestamp());
+        let crossed = COUNTDOWN_MILESTONES_NS.iter().copied().filter(|milestone| time_left <= *milestone).min();
+        if let Some(milestone) = crossed {
+            if !matches!(puzzle.last_countdown, Some(last) if last <= milestone) {
+                log_event(
+                    "countdown",
+                    json!({ "puzzle_id": puzzle_id, "milestone_ns": U64(milestone), "time_left_ns": U64(time_left) }),
+                );
+                puzzle.last_countdown = Some(milestone);
+                self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+            }
+        }
+    }
+
+    // With SplitAmongWindow, remembers the accounts credited for a solve if the reward window is still open.
+    fn add_window_solvers(&mut self, puzzle_id: &str, credited: &[AccountId]) {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        let (_, first_solved_at) = puzzle.solve_feed.get(0).expect("Puzzle hasn't been solved yet");
+        if env::block_timestamp() > first_solved_at + puzzle.reward_window_ns {
+            return;
+        }
+        for account_id in credited.iter() {
+            if !puzzle.window_solvers.contains(account_id) {
+                puzzle.window_solvers.push(account_id.clone());
+            }
+        }
+        self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+    }
+
+    // Counts a paid winner against the puzzle's `max_winners`. Returns false (and counts nothing) if the
+    // limit is already reached.
+    fn take_reward_slot(&mut self, puzzle_id: Option<&str>) -> bool {
+        let puzzle_id = match puzzle_id {
+            Some(puzzle_id) => puzzle_id,
+            None => return true,
+        };
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        if matches!(puzzle.max_winners, Some(max) if puzzle.rewarded_winners >= max) {
+            env::log_str("This puzzle has no rewards left for new winners");
+            return false;
+        }
+        puzzle.rewarded_winners += 1;
+        self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+        true
+    }
+
+    // Marks the clue solved for the account, counting it towards the clue's solves the first time.
+    fn mark_clue_solved(&mut self, puzzle_id: &str, account_id: &AccountId, clue_number: u16) {
+        if self.solved_clues.insert(&(puzzle_id.to_string(), account_id.clone(), clue_number)) {
+            let key = (puzzle_id.to_string(), clue_number);
+            self.clue_solves.insert(&key, &(self.clue_solves.get(&key).unwrap_or(0) + 1));
+        }
+    }
+
+    // Sum of the bonuses of the puzzle's optional clues the winner hasn't solved.
+    fn withheld_bonuses(&self, puzzle_id: &str, winner: &AccountId) -> Balance {
+        self.get_puzzle(puzzle_id)
+            .clues
+            .iter()
+            .filter(|(clue_number, _)| !self.solved_clues.contains(&(puzzle_id.to_string(), winner.clone(), **clue_number)))
+            .filter_map(|(_, clue)| clue.bonus)
+            .sum()
+    }
+
+    // Everyone who gets credit for a solve by `winner`: the whole team (except blacklisted members), or just
+    // the winner. The winner comes first, so it gets the remainder of an uneven split.
+    fn credited_accounts(&self, winner: &AccountId) -> Vec<AccountId> {
+        match self.teams.get(winner) {
+            Some(team) => {
+                let mut members = self.team_members.get(&team).unwrap_or_default();
+                members.retain(|member| member != winner && !self.blacklist.contains(member));
+                members.insert(0, winner.clone());
+                members
+            }
+            None => vec![winner.clone()],
+        }
+    }
+
+    // Pays out the prize pool (less any room kept for the random bonus), split equally between the recipients
+    // (one transfer each). The first recipient (the solver) also gets any indivisible remainder. A puzzle with
+    // its own (non-empty) pool pays from that one plus the pool of its linked puzzle, everything else from the
+    // shared pool.
+    // `withheld` (optional clue bonuses the winner didn't earn) is left in the pool.
+    fn pay_reward(&mut self, recipients: &[AccountId], first_solver: bool, puzzle_id: Option<&str>, withheld: Balance) {
+        let puzzle = puzzle_id.map(|puzzle_id| self.get_puzzle(puzzle_id));
+        let own_pool = puzzle.as_ref().map(|puzzle| puzzle.prize_pool).unwrap_or(0);
+        let linked_puzzle_id = puzzle.and_then(|puzzle| puzzle.linked_puzzle);
+        let linked_pool = linked_puzzle_id
+            .as_ref()
+            .map(|id| self.get_puzzle(id))
+            .filter(|linked| linked.reward_token.is_none())
+            .map(|linked| linked.prize_pool)
+            .unwrap_or(0);
+        let puzzle_pools = own_pool + linked_pool;
+        let pool = if puzzle_pools > 0 { puzzle_pools } else { self.prize_pool };
+        let mut payout = pool.saturating_sub(withheld);
+        if let Some((min, max)) = self.bonus_bps {
+            let base = payout * 10_000 / max as Balance;
+            payout = if first_solver {
+                let multiplier = bonus_multiplier_bps(&env::random_seed(), min, max);
+                base * multiplier as Balance / 10_000
+            } else {
+                base
+            };
+        }
+        let rollover = match self.rollover_puzzle {
+            Some(_) => payout * self.rollover_bps as Balance / 10_000,
+            None => 0,
+        };
+        if payout == 0 {
+            return;
+        }
+        // the remainder of an uneven split goes to the first recipient, so no yocto is lost: the account that
+        // solved (see `credited_accounts`), or the earliest solver when `finalize_rewards` splits a window
+        let share = (payout - rollover) / recipients.len() as Balance;
+        let remainder = (payout - rollover) % recipients.len() as Balance;
+        let mut paid = 0;
+        for (i, recipient) in recipients.iter().enumerate() {
+            let due = if i == 0 { share + remainder } else { share };
+            let earned = self.earnings.get(recipient).unwrap_or(0);
+            let amount = match self.max_reward_per_account {
+                Some(max) => due.min(max.saturating_sub(earned)),
+                None => due,
+            };
+            if amount == 0 {
+                continue;
+            }
+            if amount < self.min_payout {
+                env::log_str(&format!("Reward of {} is below the minimum payout, it stays in the pool", amount));
+                continue;
+            }
+            if self.vesting_ns > 0 {
+                self.add_vesting(recipient, amount);
+            } else {
+                Promise::new(recipient.clone()).transfer(amount);
+            }
+            self.earnings.insert(recipient, &(earned + amount));
+            paid += amount;
+        }
+        // nothing rolls over when every recipient was skipped (caps, minimum payout)
+        let rollover = if paid > 0 { rollover } else { 0 };
+        let paid = paid + rollover;
+        if puzzle_pools > 0 {
+            // the puzzle's own pool first, the rest from the linked one
+            let from_own = paid.min(own_pool);
+            if let Some(puzzle_id) = puzzle_id {
+                let mut puzzle = self.get_puzzle(puzzle_id);
+                puzzle.prize_pool -= from_own;
+                self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+            }
+            if let Some(linked_puzzle_id) = linked_puzzle_id {
+                let mut linked_puzzle = self.get_puzzle(&linked_puzzle_id);
+                linked_puzzle.prize_pool -= paid - from_own;
+                self.puzzles.insert(&linked_puzzle_id, &linked_puzzle);
+            }
+        } else {
+            self.prize_pool -= paid;
+        }
+        if let Some(next_puzzle_id) = self.rollover_puzzle.clone() {
+            if rollover > 0 {
+                let mut next_puzzle = self.get_puzzle(&next_puzzle_id);
+                next_puzzle.prize_pool += rollover;
+                self.puzzles.insert(&next_puzzle_id, &next_puzzle);
+                env::log_str(&format!("{} rolled over to puzzle {}", rollover, next_puzzle_id));
+            }
+        }
+    }
+
+    // Pays the puzzle's token pool out in equal shares with one `ft_transfer` each. Any indivisible remainder
+    // stays in the pool. Recipients must be registered with the token contract or their transfer fails.
+    fn pay_token_reward(&mut self, recipients: &[AccountId], puzzle_id: &str, reward_token: AccountId) {
+        let mut puzzle = self.get_puzzle(puzzle_id);
+        let share = puzzle.prize_pool / recipients.len() as Balance;
+        if share == 0 {
+            return;
+        }
+        for recipient in recipients.iter() {
+            let args = json!({ "receiver_id": recipient, "amount": U128(share) }).to_string().into_bytes();
+            Promise::new(reward_token.clone()).function_call("ft_transfer".to_string(), args, 1, Gas(GAS_FOR_FT_TRANSFER));
+        }
+        puzzle.prize_pool -= share * recipients.len() as Balance;
+        self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+    }
+
+    // Starts vesting `amount` for `account_id`. Anything still unclaimed from an earlier reward is rolled
+    // into the new entitlement, which restarts the clock.
+    fn add_vesting(&mut self, account_id: &AccountId, amount: Balance) {
+        let unclaimed = self
+            .vesting
+            .get(account_id)
+            .map(|entitlement| entitlement.total - entitlement.claimed)
+            .unwrap_or(0);
+        let entitlement = VestingEntitlement {
+            total: unclaimed + amount,
+            claimed: 0,
+            start: env::block_timestamp(),
+            duration: self.vesting_ns,
+        };
+        self.vesting.insert(account_id, &entitlement);
+    }
+
+    // Mints a prize NFT to the winner and, if enabled, chains a notification to the winner's account
+    // followed by our own callback: nft_on_mint -> resolve_mint_notification.
+    fn mint_prize(&mut self, winner: AccountId, puzzle_id: Option<&str>) {
+        let token_id = self.next_token_id.to_string();
+        self.next_token_id += 1;
+        let mut soulbound = false;
+        if let Some(puzzle_id) = puzzle_id {
+            let mut puzzle = self.get_puzzle(puzzle_id);
+            soulbound = puzzle.soulbound_prizes;
+            puzzle.tokens.push(&token_id);
+            self.puzzles.insert(&puzzle_id.to_string(), &puzzle);
+        }
+        let token = Token {
+            owner_id: winner.clone(),
+            minted_at: env::block_timestamp(),
+            claimed: false,
+            soulbound,
+            approved_account_ids: vec![],
+        };
+        self.tokens.insert(&token_id, &token);
+
+        if self.notify_on_mint {
+            let args = json!({ "token_id": token_id, "owner_id": winner }).to_string().into_bytes();
+            let callback_args = json!({ "token_id": token_id, "receiver_id": winner }).to_string().into_bytes();
+            Promise::new(winner)
+                .function_call("nft_on_mint".to_string(), args, 0, Gas(GAS_FOR_NFT_ON_MINT))
+                .then(Promise::new(env::current_account_id()).function_call(
+                    "resolve_mint_notification".to_string(),
+                    callback_args,
+                    0,
+                    Gas(GAS_FOR_RESOLVE_MINT),
+                ));
+        }
+    }
+
+    fn record_attempt(&mut self, account_id: &AccountId) {
+        let now = env::block_timestamp();
+        let mut attempts = self.attempts.get(account_id).unwrap_or(Attempts {
+            count: 0,
+            first_guess_at: now,
+            last_guess_at: now,
+            streak: 0,
+        });
+        let (today, last_day) = (now / DAY_NS, attempts.last_guess_at / DAY_NS);
+        if attempts.count == 0 || today > last_day + 1 {
+            attempts.streak = 1; // first guess, or a day was skipped
+        } else if today == last_day + 1 {
+            attempts.streak += 1;
+        }
+        attempts.count += 1;
+        attempts.last_guess_at = now;
+        self.attempts.insert(account_id, &attempts);
+    }
+
+    fn get_puzzle(&self, puzzle_id: &str) -> Puzzle {
+        self.puzzles.get(&puzzle_id.to_string()).expect("Puzzle not found")
+    }
+}
+
+// Logs a NEP-297 event of this contract.
+fn log_event(event: &str, data: near_sdk::serde_json::Value) {
+    let event = json!({
+        "standard": "crossword",
+        "version": "1.0.0",
+        "event": event,
+        "data": [data],
+    });
+    env::log_str(&format!("EVENT_JSON:{}", event));
+}
+
+// Applies the rules in a fixed order: NFC, punctuation, case, whitespace.
+fn normalize(answer: &str, rules: &NormalizeRules) -> String {
+    let mut normalized = if rules.unicode_nfc { nfc(answer) } else { answer.to_string() };
+    if rules.strip_punctuation {
+        normalized.retain(|c| c.is_alphanumeric() || c.is_whitespace());
+    }
+    if rules.lowercase {
+        normalized = normalized.to_lowercase();
+    }
+    if rules.collapse_whitespace {
+        // also trims, split_whitespace skips leading and trailing whitespace
+        normalized = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    if rules.trim {
+        normalized = normalized.trim().to_string();
+    }
+    normalized
+}
+
+// Rules this build can't apply are refused when they're set, rather than on the first guess.
+fn assert_supported_rules(rules: &NormalizeRules) {
+    require!(
+        !rules.unicode_nfc || cfg!(feature = "unicode-normalization"),
+        "This contract was built without the unicode-normalization feature"
+    );
+}
+
+#[cfg(feature = "unicode-normalization")]
+fn nfc(answer: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    answer.nfc().collect()
+}
+
+#[cfg(not(feature = "unicode-normalization"))]
+fn nfc(_answer: &str) -> String {
+    panic!("This contract was built without the unicode-normalization feature");
+}
+
+// Computed in wasm by the pure-Rust `blake2` crate, so it's behind a feature to keep the contract small.
+#[cfg(feature = "blake2")]
+fn blake2b(data: &[u8]) -> Vec<u8> {
+    use blake2::{Blake2b512, Digest};
+    Blake2b512::digest(data).to_vec()
+}
+
+#[cfg(not(feature = "blake2"))]
+fn blake2b(_data: &[u8]) -> Vec<u8> {
+    panic!("This contract was built without the blake2 feature");
+}
+
+#[cfg(feature = "ed25519-dalek")]
+fn verify_ed25519(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    use ed25519_dalek::{PublicKey, Signature, Verifier};
+    use std::convert::TryFrom;
+    match (PublicKey::from_bytes(public_key), Signature::try_from(signature)) {
+        (Ok(public_key), Ok(signature)) => public_key.verify(message, &signature).is_ok(),
+        _ => false,
+    }
+}
+
+#[cfg(not(feature = "ed25519-dalek"))]
+fn verify_ed25519(_public_key: &[u8], _message: &[u8], _signature: &[u8]) -> bool {
+    panic!("This contract was built without the ed25519-dalek feature");
+}
+
+// Formats a yoctoNEAR amount as NEAR with all 24 decimals, trailing zeros trimmed ("0" for zero).
+fn format_near(yocto: Balance) -> String {
+    const YOCTO_PER_NEAR: Balance = 10u128.pow(24);
+    let whole = yocto / YOCTO_PER_NEAR;
+    let fraction = yocto % YOCTO_PER_NEAR;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let decimals = format!("{:024}", fraction);
+    format!("{}.{}", whole, decimals.trim_end_matches('0'))
+}
+
+fn validate_meta(meta: &PuzzleMeta) {
+    if let Some(callback_url) = &meta.callback_url {
+        assert!(callback_url.len() <= MAX_CALLBACK_URL_LEN, "Callback URL is too long");
+    }
+    if let Some(theme) = &meta.theme {
+        assert!(is_valid_theme(theme), "Theme must be a hex color like #1e90ff or a lowercase name like dark-mode");
+    }
+}
+
+// A hex color with 3 or 6 digits after the '#', or a name of lowercase letters, digits and dashes.
+fn is_valid_theme(theme: &str) -> bool {
+    match theme.strip_prefix('#') {
+        Some(hex) => (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => {
+            !theme.is_empty()
+                && theme.len() <= MAX_THEME_NAME_LEN
+                && theme.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        }
+    }
+}
+
+// Picks a multiplier between `min_bps` and `max_bps` (inclusive) from the first 8 bytes of the block's random seed.
+fn bonus_multiplier_bps(seed: &[u8], min_bps: u32, max_bps: u32) -> u32 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&seed[..8]);
+    let random = u64::from_le_bytes(bytes);
+    min_bps + (random % (max_bps - min_bps + 1) as u64) as u32
+}
+
+// Small, fast PRNG (not secure, only used for cosmetic shuffling). Advances `state` and returns the next number.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// ---------------------------------------------- TESTS ----------------------------------------------------------
+// use the attribute below for unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{get_created_receipts, get_logs, testing_env_with_promise_results, VMContextBuilder};
+    use near_sdk::{testing_env, AccountId};
+
+    #[test] // note the button below 'Run test' (but for some reason runs all tests, not just current one)
+    fn debug_get_hash() {
+        // Basic set up for a unit test
+        testing_env!(VMContextBuilder::new().build()); // create some basic context for a transaction, then sets up the testing environment.
+        // Using a unit test to rapidly debug and iterate
+        let debug_solution = "near nomicon ref finance";
+        let debug_hash_bytes = env::sha256(debug_solution.as_bytes());
+        let debug_hash_string = hex::encode(debug_hash_bytes);
+        println!("Let's debug: {:?}", debug_hash_string); // Let's debug: "69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f"
+    }
+    
+    const SOLUTION_HASH: &str = "69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f"; // near nomicon ref finance
+
+    // Commitments for `set_clue_letters`, salting the letter at position i with "salt{i}".
+    fn letter_commitments(answer: &str) -> Vec<String> {
+        answer
+            .chars()
+            .enumerate()
+            .map(|(i, letter)| hex::encode(env::sha256(format!("{}salt{}", letter, i).as_bytes())))
+            .collect()
+    }
+
+    fn test_meta() -> PuzzleMeta {
+        PuzzleMeta {
+            title: "Test puzzle".to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Unwraps the result of a guess which didn't need a cross-contract call
+    fn guess_result(result: PromiseOrValue<bool>) -> bool {
+        match result {
+            PromiseOrValue::Value(correct) => correct,
+            PromiseOrValue::Promise(_) => panic!("Expected an immediate result"),
+        }
+    }
+
+    // This get_context is typically included in all unit tests, i.e. set up a mock context:
+    fn get_context(predecessor: AccountId) -> VMContextBuilder { // provide a `predecessor` here, it'll modify the default context
+        let mut builder = VMContextBuilder::new();
+        builder.predecessor_account_id(predecessor);
+        builder
+    }
+    
+    #[test]
+    fn check_guess_solution() {
+        // Get Alice as an account ID
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        // Set up the testing context and unit test environment
+        let context = get_context(alice);
+        testing_env!(context.build());
+
+        // Set up contract object and call the new method
+        let mut contract = Contract::new(
+            "69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string(), // near nomicon ref finance 69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f
+        );
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        assert_eq!(get_logs(), ["Try again."], "Expected a failure log."); //Asserts that two expressions are equal to each other 
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        //assert!(ans, true); // Asserts that a boolean expression is true at runtime.
+        assert_eq!( 
+            get_logs(), ["Try again.", "You guessed right!"],
+            "Expected a successful log after the previous failed log."
+        );
+    }
+
+    #[test]
+    fn check_estimated_guess_gas() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        let base_gas = contract.estimated_guess_gas();
+        contract.set_features(false, true, false); // alice deployed the contract so she is the owner
+        assert!(contract.estimated_guess_gas() > base_gas, "Minting should need more gas.");
+    }
+
+    #[test]
+    #[should_panic(expected = "Clue already exists")]
+    fn check_add_clue() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "a".repeat(64), None, 1);
+        contract.add_clue("puzzle1".to_string(), 2, "b".repeat(64), None, 1);
+        assert_eq!(contract.get_puzzle("puzzle1").clues.len(), 2);
+        contract.add_clue("puzzle1".to_string(), 1, "c".repeat(64), None, 1); // duplicate clue number
+    }
+
+    #[test]
+    fn check_guess_clue_with_score() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near" and "finance"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 3);
+        contract.add_clue("puzzle1".to_string(), 2, "eab762a03fd979a04cc4706e6536d382bc89d2d1356afcd054a16b2235ecd471".to_string(), None, 1);
+
+        testing_env!(get_context(bob.clone()).build());
+        assert_eq!(contract.guess_clue_with_score("puzzle1".to_string(), 2, "near".to_string(), None, None), (false, 0));
+        let (correct, score) = contract.guess_clue_with_score("puzzle1".to_string(), 1, "near".to_string(), None, None);
+        assert!(correct);
+        assert_eq!(score, 75);
+        assert_eq!(score as u32, contract.score("puzzle1".to_string(), bob) / 100);
+    }
+
+    #[test]
+    fn check_optional_clue_bonus() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near", "nomicon" and "ref"
+        let answers = [
+            "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476",
+            "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6",
+            "3ff6c05723bb069d19953340320fa9512f0be584742703e60226ded28bb43861",
+        ];
+        for (i, answer_hash) in answers.iter().enumerate() {
+            contract.add_clue("puzzle1".to_string(), i as u16 + 1, answer_hash.to_string(), None, 1);
+        }
+        contract.set_clue_bonus("puzzle1".to_string(), 3, Some(U128(2_000)));
+
+        // bob solves the required clues only: the puzzle is solved, the bonus stays in the pool
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None);
+        assert!(!contract.is_fully_solved(bob.clone(), "puzzle1".to_string()));
+        contract.guess_clue("puzzle1".to_string(), 2, "nomicon".to_string(), None, None);
+        assert!(contract.is_fully_solved(bob.clone(), "puzzle1".to_string()));
+        assert_eq!(contract.rank_of("puzzle1".to_string(), bob), Some(1));
+        assert_eq!(get_created_receipts()[0].actions, vec![VmAction::Transfer { deposit: 8_000 }]);
+
+        // carol solves the optional clue too, so she gets the bonus
+        testing_env!(get_context(carol.clone()).build());
+        for (clue_number, answer) in [(3, "ref"), (1, "near"), (2, "nomicon")].iter() {
+            contract.guess_clue("puzzle1".to_string(), *clue_number, answer.to_string(), None, None);
+        }
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, carol);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 2_000 }]);
+    }
+
+    #[test]
+    fn check_clue_guess_rules() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.set_guess_fee(U128(100));
+
+        // solving the only clue wins the pool, so it costs the guess fee like guessing the solution
+        testing_env!(get_context(bob.clone()).build());
+        let unpaid = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None)
+        }));
+        assert!(unpaid.is_err(), "A clue guess without the fee is rejected");
+
+        testing_env!(get_context(bob.clone()).attached_deposit(100).build());
+        assert!(!contract.guess_clue("puzzle1".to_string(), 1, "ethereum".to_string(), None, None));
+        assert_eq!(contract.get_attempts(bob), 1);
+        assert!(contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None));
+        assert_eq!(contract.balance_breakdown().collected_fees, U128(200));
+        assert_eq!(get_created_receipts()[0].actions, vec![VmAction::Transfer { deposit: 10_000 }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Eliminated from this puzzle")]
+    fn check_sudden_death_clue() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.set_sudden_death("puzzle1".to_string(), true);
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        assert!(!contract.guess_clue("puzzle1".to_string(), 1, "ethereum".to_string(), None, None));
+        contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None);
+    }
+
+    #[test]
+    fn check_weighted_score() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near" and "finance"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 3);
+        contract.add_clue("puzzle1".to_string(), 2, "eab762a03fd979a04cc4706e6536d382bc89d2d1356afcd054a16b2235ecd471".to_string(), None, 1);
+
+        testing_env!(get_context(bob.clone()).build());
+        assert!(contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None));
+        testing_env!(get_context(carol.clone()).build());
+        assert!(contract.guess_clue("puzzle1".to_string(), 2, "finance".to_string(), None, None));
+
+        assert_eq!(contract.score("puzzle1".to_string(), bob.clone()), 7_500);
+        assert_eq!(contract.score("puzzle1".to_string(), carol.clone()), 2_500);
+        assert!(contract.score("puzzle1".to_string(), bob) > contract.score("puzzle1".to_string(), carol));
+    }
+
+    #[test]
+    fn check_unsolved_clues() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near", "nomicon", "ref" and "finance"
+        let answers = [
+            "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476",
+            "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6",
+            "3ff6c05723bb069d19953340320fa9512f0be584742703e60226ded28bb43861",
+            "eab762a03fd979a04cc4706e6536d382bc89d2d1356afcd054a16b2235ecd471",
+        ];
+        for (i, answer_hash) in answers.iter().enumerate() {
+            contract.add_clue("puzzle1".to_string(), i as u16 + 1, answer_hash.to_string(), None, 1);
+        }
+
+        testing_env!(get_context(bob.clone()).build());
+        assert!(contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None));
+        assert!(contract.guess_clue("puzzle1".to_string(), 3, "ref".to_string(), None, None));
+        assert_eq!(contract.unsolved_clues(bob, "puzzle1".to_string()), vec![2, 4]);
+    }
+
+    #[test]
+    fn check_clue_solve_counts() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near", "nomicon" and "ref"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.add_clue("puzzle1".to_string(), 2, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.add_clue("puzzle1".to_string(), 3, "3ff6c05723bb069d19953340320fa9512f0be584742703e60226ded28bb43861".to_string(), None, 1);
+
+        for player in ["bob.testnet", "carol.testnet", "dave.testnet"].iter() {
+            testing_env!(get_context(AccountId::new_unchecked(player.to_string())).build());
+            contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None);
+        }
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_clue("puzzle1".to_string(), 2, "nomicon".to_string(), None, None);
+        contract.guess_clue("puzzle1".to_string(), 2, "nomicon".to_string(), None, None); // solving it again doesn't count
+        contract.guess_clue("puzzle1".to_string(), 3, "wrong".to_string(), None, None);
+
+        assert_eq!(contract.clue_solve_counts("puzzle1".to_string()), vec![(1, 3), (2, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn check_validate_grid() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert!(!contract.validate_grid("puzzle1".to_string()), "No dimensions announced yet");
+
+        contract.set_grid_dimensions("puzzle1".to_string(), 2, 3);
+        contract.set_grid("puzzle1".to_string(), vec![vec![true, true, false], vec![false, true, true]]);
+        assert_eq!(contract.grid_dimensions("puzzle1".to_string()), Some((2, 3)));
+        assert!(contract.validate_grid("puzzle1".to_string()));
+
+        contract.set_grid("puzzle1".to_string(), vec![vec![true, true, false], vec![false, true]]);
+        assert!(!contract.validate_grid("puzzle1".to_string()), "Second row is too short");
+        contract.set_grid("puzzle1".to_string(), vec![vec![false; 3], vec![false; 3]]);
+        assert!(!contract.validate_grid("puzzle1".to_string()), "Nothing to fill in");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid hash")]
+    fn check_add_clue_invalid_hash() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "near".to_string(), None, 1);
+    }
+
+    #[test]
+    fn check_set_clues() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "a".repeat(64), None, 1);
+        // sha256 of "near", "nomicon", "ref" and "finance"
+        contract.set_clues(
+            "puzzle1".to_string(),
+            vec![
+                (1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string()),
+                (2, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string()),
+                (3, "3ff6c05723bb069d19953340320fa9512f0be584742703e60226ded28bb43861".to_string()),
+                (4, "eab762a03fd979a04cc4706e6536d382bc89d2d1356afcd054a16b2235ecd471".to_string()),
+            ],
+        );
+
+        testing_env!(get_context(bob.clone()).build());
+        for (clue_number, answer) in [(1, "near"), (2, "nomicon"), (3, "ref"), (4, "finance")].iter() {
+            assert!(contract.guess_clue("puzzle1".to_string(), *clue_number, answer.to_string(), None, None));
+        }
+        assert!(contract.unsolved_clues(bob, "puzzle1".to_string()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid hash")]
+    fn check_set_clues_invalid_hash() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_clues("puzzle1".to_string(), vec![(1, "a".repeat(64)), (2, "not a hash".to_string())]);
+    }
+
+    #[test]
+    fn check_clue_prerequisites() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near" and "nomicon"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.add_clue("puzzle1".to_string(), 2, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.set_clue_prerequisites("puzzle1".to_string(), 2, vec![1]);
+
+        testing_env!(get_context(bob.clone()).build());
+        assert!(!contract.is_clue_unlocked(bob.clone(), "puzzle1".to_string(), 2));
+        let early = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_clue("puzzle1".to_string(), 2, "nomicon".to_string(), None, None)
+        }));
+        assert!(early.is_err(), "Clue 2 is locked until clue 1 is solved");
+        assert!(contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None));
+        assert!(contract.is_clue_unlocked(bob, "puzzle1".to_string(), 2));
+        assert!(contract.guess_clue("puzzle1".to_string(), 2, "nomicon".to_string(), None, None));
+    }
+
+    #[test]
+    fn check_progress_round_trip() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near" and "nomicon"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.add_clue("puzzle1".to_string(), 2, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.add_clue("puzzle1".to_string(), 3, "a".repeat(64), None, 1);
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None);
+        contract.guess_clue("puzzle1".to_string(), 3, "ref".to_string(), None, None);
+        let blob = contract.export_progress(bob.clone());
+
+        testing_env!(get_context(alice).build());
+        contract.import_progress(carol.clone(), blob);
+        assert_eq!(contract.unsolved_clues(carol.clone(), "puzzle1".to_string()), vec![2, 3]);
+        assert_eq!(contract.get_attempts(carol.clone()), 3); // clue guesses count as attempts too
+        assert_eq!(contract.attempts_per_clue.get(&("puzzle1".to_string(), carol, 3)), Some(1));
+
+        let invalid = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.import_progress(bob.clone(), Base64VecU8(vec![1, 2, 3]))
+        }));
+        assert!(invalid.is_err(), "A malformed blob is rejected");
+    }
+
+    #[test]
+    fn check_reveal_letter() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "nomicon"
+        contract.add_clue("puzzle1".to_string(), 1, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"));
+        contract.set_letter_fee(U128(100));
+
+        let mut context = get_context(bob.clone());
+        testing_env!(context.attached_deposit(100).build());
+        contract.request_letter("puzzle1".to_string(), 1, 2, None);
+        assert_eq!(contract.revealed_letter(bob.clone(), "puzzle1".to_string(), 1, 2), None, "The host hasn't opened it yet");
+        assert_eq!(contract.letter_escrow, 100);
+        assert_eq!(contract.collected_fees, 0, "The fee is held until the letter is opened");
+        assert_eq!(contract.next_letter_fee("puzzle1".to_string(), bob.clone()).0, 200);
+        let underpaid = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.request_letter("puzzle1".to_string(), 1, 0, None)
+        }));
+        assert!(underpaid.is_err(), "The second letter costs twice as much");
+
+        testing_env!(get_context(alice.clone()).build());
+        let wrong = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.open_letter("puzzle1".to_string(), 1, 2, "x".to_string(), "salt2".to_string())
+        }));
+        assert!(wrong.is_err(), "The host can't open a different letter");
+        contract.open_letter("puzzle1".to_string(), 1, 2, "m".to_string(), "salt2".to_string());
+        assert_eq!(contract.revealed_letter(bob.clone(), "puzzle1".to_string(), 1, 2), Some('m'));
+        assert_eq!(contract.revealed_letter(alice, "puzzle1".to_string(), 1, 2), None, "Only for accounts which paid");
+        assert_eq!(contract.letter_escrow, 0);
+        assert_eq!(contract.collected_fees, 100, "Opening the letter releases its fee");
+
+        testing_env!(get_context(AccountId::new_unchecked("carol.testnet".to_string())).attached_deposit(100).build());
+        assert_eq!(contract.reveal_letter("puzzle1".to_string(), 1, 2, None), 'm', "Opened letters come back straight away");
+        assert_eq!(contract.collected_fees, 200);
+        testing_env!(context.attached_deposit(200).build());
+        let unopened = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.reveal_letter("puzzle1".to_string(), 1, 0, None)
+        }));
+        assert!(unopened.is_err(), "Letters the host hasn't opened must be requested");
+    }
+
+    #[test]
+    fn check_refund_letter() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "nomicon"
+        contract.add_clue("puzzle1".to_string(), 1, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"));
+        contract.set_letter_fee(U128(100));
+
+        let mut context = get_context(bob.clone());
+        testing_env!(context.attached_deposit(100).build());
+        contract.request_letter("puzzle1".to_string(), 1, 0, None);
+        testing_env!(context.attached_deposit(0).build());
+        let early = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.refund_letter("puzzle1".to_string(), 1, 0)
+        }));
+        assert!(early.is_err(), "The host gets a day to open the letter");
+
+        testing_env!(context.block_timestamp(LETTER_REFUND_DELAY_NS).build());
+        contract.refund_letter("puzzle1".to_string(), 1, 0);
+        assert_eq!(get_created_receipts()[0].actions, vec![VmAction::Transfer { deposit: 100 }]);
+        assert_eq!(contract.letter_escrow, 0);
+        assert_eq!(contract.collected_fees, 0);
+        assert!(contract.unlocked_hints(bob.clone(), "puzzle1".to_string()).is_empty());
+        assert_eq!(contract.next_letter_fee("puzzle1".to_string(), bob).0, 100, "A refunded letter doesn't count");
+        let again = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.refund_letter("puzzle1".to_string(), 1, 0)
+        }));
+        assert!(again.is_err(), "The fee is only refunded once");
+    }
+
+    #[test]
+    #[should_panic(expected = "Guessing is paused")]
+    fn check_reveal_letter_when_paused() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"));
+        contract.pause(None);
+        contract.reveal_letter("puzzle1".to_string(), 1, 0, None);
+    }
+
+    #[test]
+    fn check_clue_letters_locked() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "nomicon"
+        contract.add_clue("puzzle1".to_string(), 1, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"));
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"));
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.request_letter("puzzle1".to_string(), 1, 4, None);
+        testing_env!(get_context(alice).build());
+        let requested = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"))
+        }));
+        assert!(requested.is_err(), "A letter someone is waiting for can't change");
+        contract.open_letter("puzzle1".to_string(), 1, 4, "c".to_string(), "salt4".to_string());
+        let opened = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("nomicon"))
+        }));
+        assert!(opened.is_err(), "Opened letters stay opened");
+
+        contract.add_clue("puzzle1".to_string(), 2, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.freeze_puzzle("puzzle1".to_string());
+        let frozen = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_clue_letters("puzzle1".to_string(), 2, letter_commitments("nomicon"))
+        }));
+        assert!(frozen.is_err(), "Letters are fixed once the puzzle is frozen");
+    }
+
+    #[test]
+    fn check_unlocked_hints() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near" and "nomicon"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.add_clue("puzzle1".to_string(), 2, "fb9eb7cd87efaf264bd1a317e6f5e4e96966c5c7662191ecc117453bb088e1b6".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("near"));
+        contract.set_clue_letters("puzzle1".to_string(), 2, letter_commitments("nomicon"));
+
+        testing_env!(get_context(bob.clone()).build());
+        assert!(contract.unlocked_hints(bob.clone(), "puzzle1".to_string()).is_empty());
+        contract.request_letter("puzzle1".to_string(), 2, 3, None);
+        contract.request_letter("puzzle1".to_string(), 1, 0, None);
+        // "near" takes indices 0 to 3, so position 3 of "nomicon" is index 7
+        assert_eq!(contract.unlocked_hints(bob, "puzzle1".to_string()), vec![0, 7]);
+    }
+
+    #[test]
+    fn check_hint_unlocks() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "a".repeat(64), None, 1);
+        contract.set_clue_hint("puzzle1".to_string(), 1, "The protocol this crossword runs on".to_string());
+        contract.set_hint_after_failures(2);
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_clue("puzzle1".to_string(), 1, "ethereum".to_string(), None, None);
+        assert!(contract.available_hints(bob.clone(), "puzzle1".to_string()).is_empty());
+        contract.guess_clue("puzzle1".to_string(), 1, "solana".to_string(), None, None);
+        assert_eq!(
+            contract.available_hints(bob, "puzzle1".to_string()),
+            vec![(1, "The protocol this crossword runs on".to_string())]
+        );
+    }
+
+    #[test]
+    fn check_accessibility_auto_solve() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, true, false);
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "a".repeat(64), None, 1);
+        contract.set_accessibility_mode(true, 3);
+
+        testing_env!(get_context(bob.clone()).build());
+        for answer in ["ethereum", "solana", "cosmos"].iter() {
+            assert_eq!(contract.unsolved_clues(bob.clone(), "puzzle1".to_string()), vec![1]);
+            assert!(!contract.guess_clue("puzzle1".to_string(), 1, answer.to_string(), None, None));
+        }
+        assert!(contract.unsolved_clues(bob.clone(), "puzzle1".to_string()).is_empty());
+        assert!(contract.is_fully_solved(bob.clone(), "puzzle1".to_string()));
+        assert!(get_created_receipts().is_empty(), "An assisted solve isn't paid");
+        assert!(contract.puzzle_tokens("puzzle1".to_string(), 0, 10).is_empty());
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 10_000);
+    }
+
+    #[test]
+    fn check_puzzle_solution_hash() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(
+            contract.puzzle_solution_hash("puzzle1".to_string()),
+            Some("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string())
+        );
+        assert_eq!(contract.puzzle_solution_hash("unknown".to_string()), None);
+    }
+
+    #[test]
+    fn check_callback_url() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        let meta = PuzzleMeta {
+            callback_url: Some("https://example.com/solved".to_string()),
+            ..test_meta()
+        };
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), meta);
+        let stored = contract.get_puzzle_meta("puzzle1".to_string()).unwrap();
+        assert_eq!(stored.callback_url, Some("https://example.com/solved".to_string()));
+    }
+
+    #[test]
+    fn check_theme() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        let meta = PuzzleMeta {
+            theme: Some("#1E90ff".to_string()),
+            ..test_meta()
+        };
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), meta);
+        assert_eq!(contract.get_puzzle_meta("puzzle1".to_string()).unwrap().theme, Some("#1E90ff".to_string()));
+
+        for theme in ["dark-mode", "#19f"].iter() {
+            let meta = PuzzleMeta {
+                theme: Some(theme.to_string()),
+                ..test_meta()
+            };
+            contract.update_meta("puzzle1".to_string(), meta);
+            assert_eq!(contract.get_puzzle_meta("puzzle1".to_string()).unwrap().theme, Some(theme.to_string()));
+        }
+        for theme in ["#12345", "#gggggg", "Dark Mode", ""].iter() {
+            let meta = PuzzleMeta {
+                theme: Some(theme.to_string()),
+                ..test_meta()
+            };
+            let update = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.update_meta("puzzle1".to_string(), meta)));
+            assert!(update.is_err(), "{:?} is not a valid theme", theme);
+        }
+    }
+
+    #[test]
+    fn check_update_meta() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let fixed = PuzzleMeta {
+            title: "Test puzzle, typo fixed".to_string(),
+            ..test_meta()
+        };
+        contract.update_meta("puzzle1".to_string(), fixed.clone());
+        assert_eq!(contract.get_puzzle_meta("puzzle1".to_string()), Some(fixed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Puzzle is frozen")]
+    fn check_update_meta_frozen() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.freeze_puzzle("puzzle1".to_string());
+        contract.update_meta("puzzle1".to_string(), test_meta());
+    }
+
+    #[test]
+    fn check_reveal_after_deadline() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.seal_solution("puzzle1".to_string(), "near nomicon ref finance".to_string(), U64(1_000));
+
+        testing_env!(context.block_timestamp(1_000).build());
+        assert_eq!(contract.reveal_after_deadline("puzzle1".to_string()), "near nomicon ref finance");
+    }
+
+    #[test]
+    fn check_reveal_in_ns() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.seal_solution("puzzle1".to_string(), "near nomicon ref finance".to_string(), U64(1_000));
+        assert_eq!(contract.reveal_in_ns("puzzle1".to_string()), 900);
+
+        testing_env!(context.block_timestamp(5_000).build());
+        assert_eq!(contract.reveal_in_ns("puzzle1".to_string()), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Solution can't be revealed yet")]
+    fn check_reveal_too_early() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.seal_solution("puzzle1".to_string(), "near nomicon ref finance".to_string(), U64(1_000));
+
+        testing_env!(context.block_timestamp(999).build());
+        contract.reveal_after_deadline("puzzle1".to_string());
+    }
+
+    #[test]
+    fn check_tip_author() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_author("puzzle1".to_string(), carol.clone());
+
+        let mut context = get_context(AccountId::new_unchecked("bob.testnet".to_string()));
+        testing_env!(context.attached_deposit(2_000).build());
+        contract.tip_author("puzzle1".to_string());
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, carol);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 2_000 }]);
+        assert_eq!(contract.total_tips("puzzle1".to_string()).0, 2_000);
+    }
+
+    #[test]
+    fn check_active_puzzle_count() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(1_000).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("active".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("expired".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_puzzle_deadline("expired".to_string(), Some(U64(2_000)));
+        assert_eq!(contract.active_puzzle_count(), 2);
+
+        testing_env!(context.block_timestamp(2_000).build());
+        assert_eq!(contract.puzzle_status("expired".to_string()), PuzzleStatus::Expired);
+        assert_eq!(contract.active_puzzle_count(), 1);
+    }
+
+    #[test]
+    fn check_next_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        for (puzzle_id, start) in [("week3", 3_000), ("week1", 1_000), ("week2", 2_000)].iter() {
+            contract.add_puzzle(puzzle_id.to_string(), SOLUTION_HASH.to_string(), test_meta());
+            contract.set_puzzle_start(puzzle_id.to_string(), Some(U64(*start)));
+        }
+        assert_eq!(contract.next_puzzle(), Some((1_000, "week1".to_string())));
+
+        testing_env!(context.block_timestamp(1_000).build());
+        assert_eq!(contract.next_puzzle(), Some((2_000, "week2".to_string())));
+        testing_env!(context.block_timestamp(2_500).build());
+        assert_eq!(contract.next_puzzle(), Some((3_000, "week3".to_string())));
+        testing_env!(context.block_timestamp(3_000).build());
+        assert_eq!(contract.next_puzzle(), None);
+    }
+
+    #[test]
+    fn check_global_solve_rate() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        for puzzle_id in ["puzzle1", "puzzle2", "puzzle3"].iter() {
+            contract.add_puzzle(puzzle_id.to_string(), SOLUTION_HASH.to_string(), test_meta());
+        }
+        let solves = [("bob.testnet", "puzzle1"), ("carol.testnet", "puzzle1"), ("bob.testnet", "puzzle2")];
+        for (player, puzzle_id) in solves.iter() {
+            testing_env!(get_context(AccountId::new_unchecked(player.to_string())).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some(puzzle_id.to_string()), None);
+        }
+        assert_eq!(contract.global_solve_rate(), (3, 3));
+    }
+
+    #[test]
+    fn check_access_password() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let password_hash = hex::encode(env::sha256("open sesame".as_bytes()));
+        contract.set_access_password("puzzle1".to_string(), Some(password_hash));
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        let without = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None)
+        }));
+        assert!(without.is_err(), "A password is required");
+        let wrong = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let password = Some("let me in".to_string());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), password)
+        }));
+        assert!(wrong.is_err(), "The password must match");
+        let password = Some("open sesame".to_string());
+        let result = contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), password);
+        assert!(guess_result(result));
+    }
+
+    #[test]
+    fn check_clue_access_password() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("near"));
+        let password_hash = hex::encode(env::sha256("open sesame".as_bytes()));
+        contract.set_access_password("puzzle1".to_string(), Some(password_hash));
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        let clue_without = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None)
+        }));
+        assert!(clue_without.is_err(), "Clue guesses need the password");
+        let letter_without = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.reveal_letter("puzzle1".to_string(), 1, 0, None)
+        }));
+        assert!(letter_without.is_err(), "Buying letters needs the password");
+
+        let password = Some("open sesame".to_string());
+        contract.request_letter("puzzle1".to_string(), 1, 0, password.clone());
+        assert!(contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, password));
+    }
+
+    #[test]
+    fn check_global_solve_window() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.global_solve_window(), None);
+        let solves = [("bob.testnet", "puzzle2", 2_000), ("bob.testnet", "puzzle1", 5_000), ("carol.testnet", "puzzle2", 9_000)];
+        for (player, puzzle_id, now) in solves.iter() {
+            testing_env!(get_context(AccountId::new_unchecked(player.to_string())).block_timestamp(*now).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some(puzzle_id.to_string()), None);
+        }
+        assert_eq!(contract.global_solve_window(), Some((2_000, 9_000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Solution hash is already used by puzzle puzzle1")]
+    fn check_unique_solutions() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_unique_solutions(true);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+    }
+
+    #[test]
+    fn check_solve_feed() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let solvers = ["bob.testnet", "carol.testnet", "dave.testnet"];
+        for (i, solver) in solvers.iter().enumerate() {
+            let mut context = get_context(AccountId::new_unchecked(solver.to_string()));
+            testing_env!(context.block_timestamp(100 * (i as u64 + 1)).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+        assert_eq!(contract.puzzle_status("puzzle1".to_string()), PuzzleStatus::Solved);
+
+        let page = contract.solve_feed("puzzle1".to_string(), 1, 5);
+        assert_eq!(
+            page,
+            vec![
+                (AccountId::new_unchecked("carol.testnet".to_string()), 200),
+                (AccountId::new_unchecked("dave.testnet".to_string()), 300),
+            ]
+        );
+        assert_eq!(contract.solve_feed("puzzle1".to_string(), 0, 1)[0].0.as_str(), "bob.testnet");
+    }
+
+    #[test]
+    fn check_solved_event_once() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let logs = get_logs();
+        assert!(logs[0].contains(r#""old_status":"NotStarted""#) && logs[0].contains(r#""new_status":"Active""#));
+
+        let mut solved_events = 0;
+        for solver in ["bob.testnet", "carol.testnet"].iter() {
+            testing_env!(get_context(AccountId::new_unchecked(solver.to_string())).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+            contract.refresh_status("puzzle1".to_string());
+            solved_events += get_logs()
+                .iter()
+                .filter(|log| log.starts_with("EVENT_JSON:") && log.contains(r#""new_status":"Solved""#))
+                .count();
+        }
+        assert_eq!(solved_events, 1);
+    }
+
+    #[test]
+    fn check_guess_length_stats() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        for guess in ["near", "nomicon", "ref", "near nomicon ref finance", "defi"].iter() {
+            contract.guess_solution(guess.to_string(), None, Some("puzzle1".to_string()), None);
+        }
+
+        testing_env!(get_context(alice).build());
+        assert_eq!(contract.guess_length_stats("puzzle1".to_string()), vec![(3, 1), (4, 2), (7, 1), (24, 1)]);
+    }
+
+    #[test]
+    fn check_participation_rate() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let guesses = [
+            ("bob.testnet", "wrong answer here"),
+            ("carol.testnet", "another wrong one"),
+            ("bob.testnet", "still wrong"),
+            ("dave.testnet", "near nomicon ref finance"),
+        ];
+        for (player, solution) in guesses.iter() {
+            testing_env!(get_context(AccountId::new_unchecked(player.to_string())).build());
+            contract.guess_solution(solution.to_string(), None, Some("puzzle1".to_string()), None);
+        }
+        assert_eq!(contract.participation_rate("puzzle1".to_string()), (1, 3));
+    }
+
+    #[test]
+    fn check_export_leaderboard() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let solvers: Vec<AccountId> = ["carol.testnet", "bob.testnet", "dave.testnet"]
+            .iter()
+            .map(|solver| AccountId::new_unchecked(solver.to_string()))
+            .collect();
+        for solver in solvers.iter() {
+            testing_env!(get_context(solver.clone()).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+        let blob = contract.export_leaderboard("puzzle1".to_string());
+        let exported = Vec::<AccountId>::try_from_slice(&blob.0).unwrap();
+        assert_eq!(exported, solvers);
+    }
+
+    #[test]
+    fn check_anonymous_solvers() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_anonymous_solvers(true);
+        for solver in [&bob, &carol].iter() {
+            testing_env!(get_context((*solver).clone()).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+
+        let blob = contract.export_leaderboard("puzzle1".to_string());
+        let exported = Vec::<AccountId>::try_from_slice(&blob.0).unwrap();
+        assert_eq!(exported.len(), 2);
+        assert!(!exported.contains(&bob) && !exported.contains(&carol));
+        assert!(contract.solve_feed("puzzle1".to_string(), 0, 10).iter().all(|(solver, _)| solver != &bob));
+        assert_eq!(contract.solver_count("puzzle1".to_string()), 2);
+        assert_eq!(contract.prove_rank("puzzle1".to_string()), Some(2)); // called by carol
+        testing_env!(get_context(bob.clone()).build());
+        assert_eq!(contract.prove_rank("puzzle1".to_string()), Some(1));
+        let rank_of = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.rank_of("puzzle1".to_string(), bob.clone())
+        }));
+        assert!(rank_of.is_err());
+    }
+
+    #[test]
+    fn check_countdown_event_once() {
+        let hour = 60 * 60 * 1_000_000_000;
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_puzzle_deadline("puzzle1".to_string(), Some(U64(10 * hour)));
+
+        let mut countdown_events = 0;
+        // 2 hours left, then 59 and 30 minutes left
+        for now in [8 * hour, 9 * hour + hour / 60, 9 * hour + hour / 2].iter() {
+            testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).block_timestamp(*now).build());
+            contract.guess_solution("wrong answer here".to_string(), None, Some("puzzle1".to_string()), None);
+            countdown_events += get_logs().iter().filter(|log| log.contains(r#""event":"countdown""#)).count();
+        }
+        assert_eq!(countdown_events, 1);
+    }
+
+    #[test]
+    fn check_rank_of() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let solvers = ["bob.testnet", "carol.testnet", "dave.testnet"];
+        for solver in solvers.iter() {
+            testing_env!(get_context(AccountId::new_unchecked(solver.to_string())).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+        for (i, solver) in solvers.iter().enumerate() {
+            let rank = contract.rank_of("puzzle1".to_string(), AccountId::new_unchecked(solver.to_string()));
+            assert_eq!(rank, Some(i as u64 + 1));
+        }
+        assert_eq!(contract.rank_of("puzzle1".to_string(), alice), None);
+    }
+
+    #[test]
+    fn check_expected_hash() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.expected_hash("puzzle1".to_string(), "near nomicon ref finance".to_string()), SOLUTION_HASH);
+
+        let rules = NormalizeRules {
+            trim: true,
+            lowercase: true,
+            ..Default::default()
+        };
+        contract.set_normalize_rules("puzzle1".to_string(), rules, SOLUTION_HASH.to_string());
+        let sloppy = "  NEAR Nomicon Ref Finance ".to_string();
+        assert_eq!(contract.expected_hash("puzzle1".to_string(), sloppy.clone()), SOLUTION_HASH);
+        assert!(guess_result(contract.guess_solution(sloppy, None, Some("puzzle1".to_string()), None)));
+
+        contract.set_hash_algo(HashAlgo::Keccak256);
+        assert_ne!(contract.expected_hash("puzzle1".to_string(), "near nomicon ref finance".to_string()), SOLUTION_HASH);
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn check_blake2b_solution() {
+        // BLAKE2b-512 of "near nomicon ref finance"
+        let blake2b_hash = "6d330a71e68bbb7aa601ca5769b7187e79193e3c53bfb75353645f62970adb8aca2bdd40c8eafe4bff7ca1b512862860b0b04a6314435e0b5949aabec5bfb4eb";
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(blake2b_hash.to_string());
+        contract.set_hash_algo(HashAlgo::Blake2b);
+        assert!(guess_result(contract.guess_solution("near nomicon ref finance".to_string(), None, None, None)));
+    }
+
+    #[cfg(not(feature = "blake2"))]
+    #[test]
+    #[should_panic(expected = "This contract was built without the blake2 feature")]
+    fn check_blake2b_without_feature() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_hash_algo(HashAlgo::Blake2b);
+    }
+
+    // Host key pair for the attestation tests, from a fixed secret
+    #[cfg(feature = "ed25519-dalek")]
+    fn host_keypair() -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    #[cfg(feature = "ed25519-dalek")]
+    #[test]
+    fn check_guess_with_attestation() {
+        use ed25519_dalek::Signer;
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let keypair = host_keypair();
+        contract.set_attestation_key(Some(keypair.public.to_bytes().to_vec()));
+
+        let attestation = br#"{"account_id": "bob.testnet", "puzzle_id": "puzzle1"}"#.to_vec();
+        let signature = keypair.sign(&attestation).to_bytes().to_vec();
+        testing_env!(get_context(bob.clone()).build());
+        assert!(contract.guess_with_attestation(attestation, signature, None));
+        assert_eq!(contract.rank_of("puzzle1".to_string(), bob), Some(1));
+    }
+
+    #[cfg(feature = "ed25519-dalek")]
+    #[test]
+    #[should_panic(expected = "Account is not on the allowlist")]
+    fn check_attestation_respects_allowlist() {
+        use ed25519_dalek::Signer;
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_allowlist_only("puzzle1".to_string(), true);
+        let keypair = host_keypair();
+        contract.set_attestation_key(Some(keypair.public.to_bytes().to_vec()));
+
+        let attestation = br#"{"account_id": "bob.testnet", "puzzle_id": "puzzle1"}"#.to_vec();
+        let signature = keypair.sign(&attestation).to_bytes().to_vec();
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_with_attestation(attestation, signature, None);
+    }
+
+    #[cfg(feature = "ed25519-dalek")]
+    #[test]
+    #[should_panic(expected = "Invalid attestation signature")]
+    fn check_guess_with_bad_attestation() {
+        use ed25519_dalek::Signer;
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let keypair = host_keypair();
+        contract.set_attestation_key(Some(keypair.public.to_bytes().to_vec()));
+
+        // bob reuses the signature the host made for carol's solve
+        let attestation = br#"{"account_id": "bob.testnet", "puzzle_id": "puzzle1"}"#.to_vec();
+        let signature = keypair.sign(br#"{"account_id": "carol.testnet", "puzzle_id": "puzzle1"}"#).to_bytes().to_vec();
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_with_attestation(attestation, signature, None);
+    }
+
+    #[test]
+    fn check_per_puzzle_normalize_rules() {
+        let dont_hash = "f9e0a37fc2800a6256ceb7d76820ec0b9224755b4da3a0638d63843d2925fbdc"; // sha256 of "dont"
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("lenient".to_string(), dont_hash.to_string(), test_meta());
+        contract.add_puzzle("strict".to_string(), dont_hash.to_string(), test_meta());
+        let rules = NormalizeRules {
+            strip_punctuation: true,
+            ..Default::default()
+        };
+        contract.set_normalize_rules("lenient".to_string(), rules, dont_hash.to_string());
+        assert_eq!(contract.get_normalize_rules("lenient".to_string()), rules);
+        assert_eq!(contract.get_normalize_rules("strict".to_string()), NormalizeRules::default());
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        assert!(guess_result(contract.guess_solution("don't".to_string(), None, Some("lenient".to_string()), None)));
+        assert!(!guess_result(contract.guess_solution("don't".to_string(), None, Some("strict".to_string()), None)));
+    }
+
+    #[cfg(not(feature = "unicode-normalization"))]
+    #[test]
+    #[should_panic(expected = "This contract was built without the unicode-normalization feature")]
+    fn check_unicode_nfc_without_feature() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        let rules = NormalizeRules {
+            unicode_nfc: true,
+            ..Default::default()
+        };
+        contract.set_normalization(rules);
+    }
+
+    #[test]
+    fn check_set_normalize_rules_rehash() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let rules = NormalizeRules {
+            strip_punctuation: true,
+            ..Default::default()
+        };
+        let new_hash = "f9e0a37fc2800a6256ceb7d76820ec0b9224755b4da3a0638d63843d2925fbdc"; // sha256 of "dont"
+        contract.set_normalize_rules("puzzle1".to_string(), rules, new_hash.to_string());
+        assert_eq!(contract.puzzle_solution_hash("puzzle1".to_string()), Some(new_hash.to_string()));
+        assert_eq!(contract.expected_hash("puzzle1".to_string(), "don't".to_string()), new_hash);
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        assert!(guess_result(contract.guess_solution("don't".to_string(), None, Some("puzzle1".to_string()), None)));
+    }
+
+    #[test]
+    fn check_set_normalize_rules_guards() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        let invalid = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_normalize_rules("puzzle1".to_string(), NormalizeRules::default(), "not a hash".to_string())
+        }));
+        assert!(invalid.is_err(), "The new hash is validated");
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        testing_env!(get_context(alice).build());
+        let solved = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_normalize_rules("puzzle1".to_string(), NormalizeRules::default(), "b".repeat(64))
+        }));
+        assert!(solved.is_err(), "The answer of a solved puzzle can't change");
+        assert_eq!(contract.puzzle_solution_hash("puzzle1".to_string()), Some(SOLUTION_HASH.to_string()));
+    }
+
+    #[test]
+    fn check_nft_gated_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let nft_contract = AccountId::new_unchecked("nft.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_required_nft("puzzle1".to_string(), Some(nft_contract.clone()));
+
+        // The guess becomes a call to the NFT contract followed by our callback
+        testing_env!(get_context(bob.clone()).build());
+        let result = contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+        drop(result); // dropping a promise schedules it
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, nft_contract);
+        assert!(format!("{:?}", receipts[0].actions[0]).contains("nft_tokens_for_owner"));
+
+        // Mocked NFT contract: bob holds no tokens, access denied
+        let context = get_context(env::current_account_id());
+        testing_env_with_promise_results(context.build(), PromiseResult::Successful(b"[]".to_vec()));
+        let fee = U128(0);
+        assert!(!contract.resolve_gated_guess(bob.clone(), "puzzle1".to_string(), "near nomicon ref finance".to_string(), fee));
+        assert_eq!(contract.puzzle_status("puzzle1".to_string()), PuzzleStatus::Active);
+
+        // bob holds a token, the guess goes through
+        let tokens = br#"[{"token_id": "1", "owner_id": "bob.testnet"}]"#.to_vec();
+        testing_env_with_promise_results(context.build(), PromiseResult::Successful(tokens));
+        assert!(contract.resolve_gated_guess(bob.clone(), "puzzle1".to_string(), "near nomicon ref finance".to_string(), fee));
+        assert_eq!(contract.have_solved(vec![bob]), vec![true]);
+    }
+
+    #[test]
+    fn check_nft_gated_guess_after_cancel() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_required_nft("puzzle1".to_string(), Some(AccountId::new_unchecked("nft.testnet".to_string())));
+        contract.set_guess_fee(U128(100));
+
+        testing_env!(get_context(bob.clone()).attached_deposit(100).build());
+        drop(contract.guess_solution("wrong answer here".to_string(), None, Some("puzzle1".to_string()), None));
+        assert_eq!(contract.collected_fees, 100);
+
+        // the owner cancels (refunding bob) before the NFT contract answers
+        testing_env!(get_context(alice).build());
+        contract.cancel_puzzle("puzzle1".to_string());
+        assert_eq!(contract.collected_fees, 0);
+
+        testing_env_with_promise_results(get_context(env::current_account_id()).build(), PromiseResult::Successful(b"[]".to_vec()));
+        assert!(!contract.resolve_gated_guess(bob, "puzzle1".to_string(), "wrong answer here".to_string(), U128(100)));
+        assert_eq!(contract.collected_fees, 0);
+        assert!(get_created_receipts().is_empty(), "No second refund");
+    }
+
+    #[test]
+    #[should_panic(expected = "Puzzle is NFT-gated, guess its solution instead")]
+    fn check_nft_gated_clue() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.set_required_nft("puzzle1".to_string(), Some(AccountId::new_unchecked("nft.testnet".to_string())));
+
+        // solving the only clue would solve the puzzle without the NFT check
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None);
+    }
+
+    #[test]
+    fn check_answer_length_hint() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("puzzle1".to_string(), 1, "a".repeat(64), Some(7), 1);
+        assert_eq!(contract.answer_length_hint("puzzle1".to_string(), 1), 7);
+    }
+
+    #[test]
+    fn check_have_solved() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None); // alice fails
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None); // bob solves
+        testing_env!(get_context(carol.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None); // carol solves
+
+        assert_eq!(contract.have_solved(vec![bob, alice, carol]), vec![true, false, true]);
+    }
+
+    #[test]
+    fn check_auto_unpause() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(1_000).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.pause(Some(U64(5_000)));
+        assert!(contract.is_paused());
+
+        // Time travel past the auto-unpause time, no explicit unpause needed
+        testing_env!(context.block_timestamp(5_000).build());
+        assert!(!contract.is_paused());
+        assert!(guess_result(contract.guess_solution("near nomicon ref finance".to_string(), None, None, None)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Guesses must be signed by the calling account")]
+    fn check_humans_only() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_humans_only(true);
+
+        // bob signs, but the call reaches us through a bot contract
+        let mut context = get_context(AccountId::new_unchecked("bot.testnet".to_string()));
+        testing_env!(context.signer_account_id(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+    }
+
+    #[test]
+    fn check_humans_only_clues() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        // sha256 of "near"
+        contract.add_clue("puzzle1".to_string(), 1, "46ba34770bccfde756708d47d83fb7c8257fe8a4b3a35f25d385a8284021f476".to_string(), None, 1);
+        contract.set_clue_letters("puzzle1".to_string(), 1, letter_commitments("near"));
+        contract.set_humans_only(true);
+
+        // bob signs, but the calls reach us through a bot contract
+        let mut context = get_context(AccountId::new_unchecked("bot.testnet".to_string()));
+        testing_env!(context.signer_account_id(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        let clue = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None)
+        }));
+        assert!(clue.is_err(), "Relayed clue guesses are rejected");
+        let letter = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.reveal_letter("puzzle1".to_string(), 1, 0, None)
+        }));
+        assert!(letter.is_err(), "Relayed letter purchases are rejected");
+    }
+
+    #[test]
+    fn check_playable_puzzles() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let dave = AccountId::new_unchecked("dave.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("open".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("members".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("later".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_allowlist_only("members".to_string(), true);
+        contract.add_to_allowlist("members".to_string(), vec![bob.clone()]);
+        contract.set_puzzle_start("later".to_string(), Some(U64(1_000)));
+        contract.add_to_blacklist(carol.clone());
+
+        let mut playable = contract.playable_puzzles(bob);
+        playable.sort();
+        assert_eq!(playable, vec!["members".to_string(), "open".to_string()]);
+        assert_eq!(contract.playable_puzzles(dave), vec!["open".to_string()]);
+        assert!(contract.playable_puzzles(carol).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is blacklisted")]
+    fn check_blacklisted_guess() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_to_blacklist(bob.clone());
+        testing_env!(get_context(bob).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is blacklisted")]
+    fn check_blacklisted_claim() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(false, true, false);
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+
+        // caught cheating after winning
+        testing_env!(get_context(alice).build());
+        contract.add_to_blacklist(bob.clone());
+        testing_env!(get_context(bob).build());
+        contract.claim_nft("0".to_string());
+    }
+
+    #[test]
+    fn check_pause_single_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.pause_puzzle("puzzle1".to_string());
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        assert!(guess_result(contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle2".to_string()), None)));
+        let paused_guess = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None)
+        }));
+        assert!(paused_guess.is_err(), "The paused puzzle can't be guessed");
+    }
+
+    #[test]
+    #[should_panic(expected = "Guessing is paused")]
+    fn check_guess_while_paused() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.pause(None);
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid or already used invite code")]
+    fn check_invite_code_single_use() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.set_require_invite(true);
+        contract.add_invite_codes(vec![hex::encode(env::sha256("golden ticket".as_bytes()))]);
+
+        contract.guess_solution("wrong answer here".to_string(), Some("golden ticket".to_string()), None, None); // code accepted
+        contract.guess_solution("wrong answer here".to_string(), Some("golden ticket".to_string()), None, None); // code already used
+    }
+
+    #[test]
+    fn check_puzzle_start() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_puzzle_start("puzzle1".to_string(), Some(U64(1_000)));
+        assert_eq!(contract.puzzle_status("puzzle1".to_string()), PuzzleStatus::NotStarted);
+        assert_eq!(contract.get_puzzle_meta("puzzle1".to_string()), Some(test_meta()));
+
+        let mut context = get_context(AccountId::new_unchecked("bob.testnet".to_string()));
+        testing_env!(context.block_timestamp(1_000).build());
+        assert!(guess_result(contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Puzzle not started")]
+    fn check_guess_before_start() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_puzzle_start("puzzle1".to_string(), Some(U64(1_000)));
+
+        let mut context = get_context(AccountId::new_unchecked("bob.testnet".to_string()));
+        testing_env!(context.block_timestamp(999).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+    }
+
+    #[test]
+    fn check_guess_window() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        assert_eq!(contract.guess_window(alice.clone()), None);
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        testing_env!(context.block_timestamp(900).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        assert_eq!(contract.guess_window(alice), Some((100, 900)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attempts are private")]
+    fn check_private_attempts() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_attempts_public(false);
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        assert_eq!(contract.get_attempts(bob.clone()), 1);
+
+        testing_env!(get_context(AccountId::new_unchecked("carol.testnet".to_string())).build());
+        contract.get_attempts(bob);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach at least the minimum stake to play")]
+    fn check_min_stake_to_play() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.set_min_stake_to_play(U128(1_000_000));
+        testing_env!(context.attached_deposit(999_999).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+    }
+
+    #[test]
+    fn check_reclaim_unclaimed_nft() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(false, true, false);
+        testing_env!(get_context(bob.clone()).block_timestamp(1_000).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        assert_eq!(contract.tokens.get(&"0".to_string()).unwrap().owner_id, bob);
+
+        testing_env!(context.block_timestamp(1_000 + NFT_RECLAIM_DELAY_NS).build());
+        contract.reclaim_unclaimed_nft("0".to_string());
+        assert_eq!(contract.tokens.get(&"0".to_string()).unwrap().owner_id, alice);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token was claimed")]
+    fn check_reclaim_transferred_nft() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(false, true, false);
+        let mut bob_context = get_context(bob);
+        testing_env!(bob_context.block_timestamp(1_000).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        testing_env!(bob_context.attached_deposit(1).build());
+        contract.nft_transfer(AccountId::new_unchecked("carol.testnet".to_string()), "0".to_string(), None);
+
+        // bob moved the token to carol, so it counts as claimed
+        testing_env!(context.block_timestamp(1_000 + NFT_RECLAIM_DELAY_NS).build());
+        contract.reclaim_unclaimed_nft("0".to_string());
+    }
+
+    #[test]
+    fn check_puzzle_tokens() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(false, true, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        for (solver, puzzle_id) in [("bob.testnet", "puzzle1"), ("carol.testnet", "puzzle2"), ("dave.testnet", "puzzle1")].iter() {
+            testing_env!(get_context(AccountId::new_unchecked(solver.to_string())).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some(puzzle_id.to_string()), None);
+        }
+        assert_eq!(contract.puzzle_tokens("puzzle1".to_string(), 0, 1), vec!["0".to_string()]);
+        assert_eq!(contract.puzzle_tokens("puzzle1".to_string(), 1, 10), vec!["2".to_string()]);
+        assert_eq!(contract.puzzle_tokens("puzzle2".to_string(), 0, 10), vec!["1".to_string()]);
+    }
+
+    #[test]
+    fn check_soulbound_prize() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(false, true, false);
+        contract.add_puzzle("regular".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("achievement".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_soulbound_prizes("achievement".to_string(), true);
+
+        let mut context = get_context(bob.clone());
+        testing_env!(context.build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("regular".to_string()), None); // token 0
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("achievement".to_string()), None); // token 1
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_transfer(carol.clone(), "0".to_string(), None);
+        assert_eq!(contract.tokens.get(&"0".to_string()).unwrap().owner_id, carol);
+
+        let transfer = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.nft_transfer(carol.clone(), "1".to_string(), None)
+        }));
+        assert!(transfer.is_err(), "A soulbound token can't be transferred");
+        let approve = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.nft_approve("1".to_string(), carol.clone())
+        }));
+        assert!(approve.is_err(), "A soulbound token can't be approved");
+        assert_eq!(contract.tokens.get(&"1".to_string()).unwrap().owner_id, bob);
+    }
+
+    #[test]
+    fn check_mint_then_notify() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.set_features(false, true, false);
+        contract.set_notify_on_mint(true);
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        assert_eq!(contract.tokens.get(&"0".to_string()).unwrap().owner_id, bob);
+
+        // First the winner is called, then our resolver
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert!(matches!(receipts[0].actions[0], VmAction::FunctionCall { .. }));
+        assert!(format!("{:?}", receipts[0].actions[0]).contains("nft_on_mint"));
+        assert_eq!(receipts[1].receiver_id, env::current_account_id());
+        assert!(format!("{:?}", receipts[1].actions[0]).contains("resolve_mint_notification"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Please wait for the cooldown before guessing again")]
+    fn check_set_cooldown() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(1_000).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.set_cooldown(500);
+        assert_eq!(contract.get_cooldown(), 500);
+
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        testing_env!(context.block_timestamp(1_500).build());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None); // cooldown over
+        testing_env!(context.block_timestamp(1_999).build());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None); // too soon
+    }
+
+    #[test]
+    fn check_clue_order() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        for clue_number in 1..=8 {
+            contract.add_clue("puzzle1".to_string(), clue_number, "a".repeat(64), None, 1);
+        }
+        let order = contract.clue_order("puzzle1".to_string(), 42);
+        assert_eq!(order, contract.clue_order("puzzle1".to_string(), 42), "Same seed, same order.");
+        assert_ne!(order, contract.clue_order("puzzle1".to_string(), 7), "Different seeds, different orders.");
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (1..=8).collect::<Vec<u16>>(), "Every clue appears exactly once.");
+    }
+
+    #[test]
+    fn check_team_split_reward() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(1_001).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.join_team(bob.clone(), "owls".to_string());
+        contract.invite_to_team("owls".to_string(), carol.clone());
+        testing_env!(get_context(carol.clone()).build());
+        contract.join_team(carol.clone(), "owls".to_string());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None); // carol solves for the team
+
+        assert_eq!(contract.have_solved(vec![bob.clone(), carol.clone()]), vec![true, true]);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        // carol solved, so the indivisible yocto goes to her
+        for (receipt, (member, share)) in receipts.iter().zip([(carol, 501), (bob, 500)].iter()) {
+            assert_eq!(&receipt.receiver_id, member);
+            assert_eq!(receipt.actions, vec![VmAction::Transfer { deposit: *share }]);
+        }
+        assert_eq!(contract.balance_breakdown().prize_pool.0, 0);
+    }
+
+    #[test]
+    fn check_blacklisted_teammate_unpaid() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(1_000).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.join_team(bob.clone(), "owls".to_string());
+        contract.invite_to_team("owls".to_string(), carol.clone());
+        testing_env!(get_context(carol.clone()).build());
+        contract.join_team(carol.clone(), "owls".to_string());
+        testing_env!(get_context(alice).build());
+        contract.add_to_blacklist(bob);
+
+        testing_env!(get_context(carol.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1, "bob's share isn't paid out");
+        assert_eq!(receipts[0].receiver_id, carol);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 1_000 }]);
+    }
+
+    #[test]
+    fn check_team_consent() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.join_team(bob.clone(), "bobs".to_string());
+
+        // carol can't invite herself into bob's team and take a share of his prizes
+        testing_env!(get_context(carol.clone()).build());
+        let uninvited = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.join_team(carol.clone(), "bobs".to_string())
+        }));
+        assert!(uninvited.is_err(), "Joining needs an invite");
+        let self_invite = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.invite_to_team("bobs".to_string(), carol.clone())
+        }));
+        assert!(self_invite.is_err(), "Only members can invite");
+        assert_eq!(contract.get_team(carol.clone()), None);
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.invite_to_team("bobs".to_string(), carol.clone());
+        testing_env!(get_context(carol.clone()).build());
+        contract.join_team(carol.clone(), "bobs".to_string());
+        assert_eq!(contract.get_team(carol.clone()), Some("bobs".to_string()));
+
+        contract.leave_team();
+        assert_eq!(contract.get_team(carol), None);
+        assert_eq!(contract.team_members.get(&"bobs".to_string()), Some(vec![bob]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Team is full, it can have at most 5 members")]
+    fn check_team_size_limit() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        for member in ["m1", "m2", "m3", "m4", "m5", "m6"].iter() {
+            contract.join_team(AccountId::new_unchecked(format!("{}.testnet", member)), "crowd".to_string());
+        }
+    }
+
+    #[test]
+    fn check_rollover() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_rollover(2_000, Some("puzzle2".to_string()));
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 8_000 }]);
+        assert_eq!(contract.puzzle_prize_pool("puzzle2".to_string()).0, 2_000);
+        assert_eq!(contract.prize_pool, 0);
+    }
+
+    #[test]
+    fn check_no_rollover_without_payout() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_rollover(2_000, Some("puzzle2".to_string()));
+        contract.set_min_payout(U128(9_000));
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert!(get_created_receipts().is_empty());
+        assert_eq!(contract.puzzle_prize_pool("puzzle2".to_string()).0, 0, "Nothing rolls over when nobody is paid");
+        assert_eq!(contract.prize_pool, 10_000);
+    }
+
+    #[test]
+    fn check_max_reward_per_account() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_max_reward_per_account(Some(U128(4_000)));
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert_eq!(get_created_receipts()[0].actions, vec![VmAction::Transfer { deposit: 4_000 }]);
+        assert_eq!(contract.get_earnings(bob.clone()).0, 4_000);
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle2".to_string()), None);
+        assert!(get_created_receipts().is_empty(), "Bob reached the cap, nothing more is paid");
+        assert_eq!(contract.prize_pool, 6_000);
+    }
+
+    #[test]
+    fn check_claim_vested() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.set_vesting(1_000);
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+
+        let mut bob_context = get_context(bob.clone());
+        testing_env!(bob_context.block_timestamp(0).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        assert!(get_created_receipts().is_empty(), "Nothing is paid out straight away.");
+
+        testing_env!(bob_context.block_timestamp(500).build()); // half way through vesting
+        assert_eq!(contract.claim_vested().0, 5_000);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 5_000 }]);
+    }
+
+    #[test]
+    fn check_cancel_puzzle_refunds() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_guess_fee(U128(100));
+
+        // bob guesses twice, carol once
+        for player in [bob.clone(), bob.clone(), carol.clone()].iter() {
+            testing_env!(get_context(player.clone()).attached_deposit(100).build());
+            contract.guess_solution("wrong answer here".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+        assert_eq!(contract.balance_breakdown().collected_fees.0, 300);
+
+        testing_env!(get_context(alice).build());
+        contract.cancel_puzzle("puzzle1".to_string());
+        assert_eq!(contract.puzzle_status("puzzle1".to_string()), PuzzleStatus::Cancelled);
+        assert_eq!(contract.balance_breakdown().collected_fees.0, 0);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 200 }]);
+        assert_eq!(receipts[1].receiver_id, carol);
+        assert_eq!(receipts[1].actions, vec![VmAction::Transfer { deposit: 100 }]);
+    }
+
+    #[test]
+    fn check_random_bonus() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.set_random_bonus(Some((10_000, 20_000))); // between 1x and 2x
+        testing_env!(context.attached_deposit(20_000).build());
+        contract.fund_prize_pool();
+
+        // Seed starting with 2_500 (little endian): multiplier = 10_000 + 2_500 % 10_001 = 12_500
+        let mut seed = [0u8; 32];
+        seed[..8].copy_from_slice(&2_500u64.to_le_bytes());
+        assert_eq!(bonus_multiplier_bps(&seed, 10_000, 20_000), 12_500);
+
+        testing_env!(get_context(bob.clone()).random_seed(seed.to_vec()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        // base = 20_000 * 10_000 / 20_000 = 10_000, times 1.25
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 12_500 }]);
+        assert_eq!(contract.balance_breakdown().prize_pool.0, 7_500);
+    }
+
+    #[test]
+    fn check_init_params() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_guess_fee(U128(1_000)); // later changes don't show up
+        let params = contract.init_params();
+        assert_eq!(params.owner_id, alice);
+        assert_eq!(params.solution_hash, SOLUTION_HASH);
+        assert_eq!(params.hash_algo, HashAlgo::Sha256);
+        assert!(!params.salted);
+        assert_eq!(params.guess_fee, U128(0));
+        assert_eq!(params.min_stake_to_play, U128(0));
+    }
+
+    #[test]
+    fn check_migrate() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(env::current_account_id()).build());
+        env::state_write(&OldContract { crossword_solution: SOLUTION_HASH.to_string() });
+
+        let mut contract = Contract::migrate(alice.clone());
+        assert_eq!(contract.get_solution(), SOLUTION_HASH);
+        assert_eq!(contract.init_params().owner_id, alice);
+        testing_env!(get_context(alice).build());
+        contract.set_guess_fee(U128(1_000)); // alice owns the migrated contract
+    }
+
+    #[test]
+    fn check_age() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).block_timestamp(1_000).build());
+        let contract = Contract::new(SOLUTION_HASH.to_string());
+        assert_eq!(contract.created_at().0, 1_000);
+        assert_eq!(contract.age_ns(), 0);
+        testing_env!(get_context(alice).block_timestamp(6_000).build());
+        assert_eq!(contract.age_ns(), 5_000);
+        assert_eq!(contract.created_at().0, 1_000);
+    }
+
+    #[test]
+    fn check_banner() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        assert_eq!(contract.get_banner(), None);
+        contract.set_banner(Some("Maintenance at 5pm".to_string()));
+        assert_eq!(contract.get_banner(), Some("Maintenance at 5pm".to_string()));
+        contract.set_banner(None);
+        assert_eq!(contract.get_banner(), None);
+        let too_long = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_banner(Some("!".repeat(MAX_BANNER_LEN + 1)))
+        }));
+        assert!(too_long.is_err());
+    }
+
+    #[test]
+    fn check_activity_streak() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        assert_eq!(contract.activity_streak(bob.clone()), 0);
+
+        // days 0, 0, 1 and 2 in a row, then day 4 after skipping day 3
+        let guesses = [(DAY_NS / 2, 1), (DAY_NS / 2 + 1, 1), (DAY_NS + 5, 2), (2 * DAY_NS + DAY_NS / 3, 3), (4 * DAY_NS, 1)];
+        for (now, streak) in guesses.iter() {
+            testing_env!(get_context(bob.clone()).block_timestamp(*now).build());
+            contract.guess_solution("wrong answer here".to_string(), None, None, None);
+            assert_eq!(contract.activity_streak(bob.clone()), *streak);
+        }
+        testing_env!(get_context(bob.clone()).block_timestamp(5 * DAY_NS).build());
+        assert_eq!(contract.activity_streak(bob.clone()), 1, "Still alive the next day");
+        testing_env!(get_context(bob.clone()).block_timestamp(6 * DAY_NS).build());
+        assert_eq!(contract.activity_streak(bob), 0);
+    }
+
+    #[test]
+    fn check_supported_standards() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let contract = Contract::new(SOLUTION_HASH.to_string());
+        let standards = contract.supported_standards();
+        assert!(standards.contains(&"nep297".to_string()));
+        assert!(!standards.contains(&"nep171".to_string()), "The NFT standard is only partly implemented");
+    }
+
+    #[test]
+    fn check_health() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let contract = Contract::new(SOLUTION_HASH.to_string());
+        let health = contract.health();
+        assert!(health.initialized);
+        assert!(!health.paused);
+        assert_eq!(health.puzzle_count, 0);
+        assert!(health.balance_ok);
+    }
+
+    #[test]
+    fn check_split_among_window() {
+        let minute = 60 * 1_000_000_000;
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let dave = AccountId::new_unchecked("dave.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_mode("puzzle1".to_string(), RewardMode::SplitAmongWindow, U64(10 * minute));
+
+        // bob and carol solve within 10 minutes, dave too late
+        for (solver, now) in [(&bob, minute), (&carol, 5 * minute), (&dave, 12 * minute)].iter() {
+            testing_env!(get_context((*solver).clone()).block_timestamp(*now).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+            assert!(get_created_receipts().is_empty(), "Nobody is paid before the window closes");
+        }
+        contract.finalize_rewards("puzzle1".to_string());
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        for (receipt, solver) in receipts.iter().zip([bob, carol].iter()) {
+            assert_eq!(&receipt.receiver_id, solver);
+            assert_eq!(receipt.actions, vec![VmAction::Transfer { deposit: 5_000 }]);
+        }
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 0);
+    }
+
+    #[test]
+    fn check_finalize_skips_blacklisted() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_mode("puzzle1".to_string(), RewardMode::SplitAmongWindow, U64(1_000));
+        for solver in [&bob, &carol].iter() {
+            testing_env!(get_context((*solver).clone()).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+
+        // carol is caught cheating before the window closes
+        testing_env!(context.attached_deposit(0).build());
+        contract.add_to_blacklist(carol);
+        testing_env!(context.block_timestamp(2_000).build());
+        contract.finalize_rewards("puzzle1".to_string());
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 10_000 }]);
+    }
+
+    #[test]
+    fn check_finalize_waits_for_rewards() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_mode("puzzle1".to_string(), RewardMode::SplitAmongWindow, U64(1_000));
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        testing_env!(get_context(bob.clone()).block_timestamp(2_000).build());
+        let finalize = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.finalize_rewards("puzzle1".to_string())
+        }));
+        assert!(finalize.is_err(), "Finalizing with rewards disabled would lose the window solvers");
+
+        testing_env!(get_context(alice).block_timestamp(2_000).build());
+        contract.set_features(true, false, false);
+        contract.finalize_rewards("puzzle1".to_string());
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 10_000 }]);
+    }
+
+    #[test]
+    fn check_split_remainder_goes_to_first_solver() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(10_001).build());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_mode("puzzle1".to_string(), RewardMode::SplitAmongWindow, U64(1_000));
+
+        for (i, solver) in ["bob.testnet", "carol.testnet", "dave.testnet"].iter().enumerate() {
+            testing_env!(get_context(AccountId::new_unchecked(solver.to_string())).block_timestamp(i as u64).build());
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        }
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).block_timestamp(2_000).build());
+        contract.finalize_rewards("puzzle1".to_string());
+
+        let payouts: Vec<Balance> = get_created_receipts()
+            .iter()
+            .map(|receipt| match receipt.actions[..] {
+                [VmAction::Transfer { deposit }] => deposit,
+                _ => panic!("Expected a transfer"),
+            })
+            .collect();
+        assert_eq!(payouts, vec![3_335, 3_333, 3_333]);
+        assert_eq!(payouts.iter().sum::<Balance>(), 10_001);
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 0);
+    }
+
+    #[test]
+    fn check_no_reward_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, true, false);
+        contract.set_guess_fee(U128(100));
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_mode("puzzle1".to_string(), RewardMode::None, U64(0));
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+        assert_eq!(contract.puzzle_view("puzzle1".to_string()).unwrap().potential_reward.0, 0);
+
+        testing_env!(get_context(bob.clone()).build());
+        let result = contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert!(guess_result(result), "No guess fee is needed");
+        assert_eq!(contract.rank_of("puzzle1".to_string(), bob), Some(1));
+        assert!(get_created_receipts().is_empty());
+        assert_eq!(contract.prize_pool, 10_000);
+        assert_eq!(contract.collected_fees, 0);
+        assert!(contract.puzzle_tokens("puzzle1".to_string(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn check_global_attempts_remaining() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.global_attempts_remaining("puzzle1".to_string()), None);
+        contract.set_max_attempts("puzzle1".to_string(), Some(2));
+
+        for (player, remaining) in [("bob.testnet", 1), ("carol.testnet", 0)].iter() {
+            testing_env!(get_context(AccountId::new_unchecked(player.to_string())).build());
+            contract.guess_solution("wrong answer here".to_string(), None, Some("puzzle1".to_string()), None);
+            assert_eq!(contract.global_attempts_remaining("puzzle1".to_string()), Some(*remaining));
+        }
+        assert_eq!(contract.puzzle_view("puzzle1".to_string()).unwrap().attempts_remaining, Some(0));
+        let guess = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None)
+        }));
+        assert!(guess.is_err(), "The cap closes the puzzle");
+    }
+
+    #[test]
+    #[should_panic(expected = "Same guess as last time")]
+    fn check_reject_repeat_guesses() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reject_repeat_guesses(true);
+
+        testing_env!(get_context(AccountId::new_unchecked("bob.testnet".to_string())).build());
+        contract.guess_solution("wrong answer here".to_string(), None, Some("puzzle1".to_string()), None);
+        contract.guess_solution("wrong answer here".to_string(), None, None, None); // another puzzle is fine
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+    }
+
+    #[test]
+    fn check_min_payout() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_min_payout(U128(1_000));
+        testing_env!(context.attached_deposit(500).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert!(get_created_receipts().is_empty());
+        assert_eq!(contract.rank_of("puzzle1".to_string(), bob.clone()), Some(1));
+        assert_eq!(contract.get_earnings(bob).0, 0);
+        assert_eq!(contract.prize_pool, 500);
+    }
+
+    #[test]
+    fn check_reclaim_pool_to_charity() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let charity = AccountId::new_unchecked("charity.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.attached_deposit(10_000).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_puzzle_deadline("puzzle1".to_string(), Some(U64(1_000)));
+        contract.set_charity_account(Some(charity.clone()));
+
+        testing_env!(context.attached_deposit(0).block_timestamp(2_000).build());
+        contract.reclaim_pool("puzzle1".to_string());
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, charity);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 10_000 }]);
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 0);
+    }
+
+    #[test]
+    fn check_author_stats() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+
+        let mut context = get_context(AccountId::new_unchecked("bob.testnet".to_string()));
+        testing_env!(context.build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        testing_env!(context.attached_deposit(300).build());
+        contract.tip_author("puzzle2".to_string());
+
+        let stats = contract.author_stats(alice);
+        assert_eq!(stats.puzzles_created, 2);
+        assert_eq!(stats.total_solvers, 1);
+        assert_eq!(stats.total_tips.0, 300);
+    }
+
+    #[test]
+    fn check_author_puzzle_count() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.author_puzzle_count(alice.clone()), 2);
+
+        contract.remove_puzzle("puzzle2".to_string());
+        assert_eq!(contract.author_puzzle_count(alice.clone()), 1);
+        assert_eq!(contract.get_puzzle_meta("puzzle2".to_string()), None);
+        assert_eq!(contract.author_stats(alice).puzzles_created, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Puzzle id was used by a removed puzzle")]
+    fn check_removed_puzzle_id_not_reused() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.remove_puzzle("puzzle1".to_string());
+        // per-account records of the old puzzle (clue progress, allowlist) would carry over
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+    }
+
+    #[test]
+    fn check_transfer_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.transfer_puzzle("puzzle1".to_string(), bob.clone());
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.add_clue("puzzle1".to_string(), 1, "a".repeat(64), None, 1);
+        contract.transfer_puzzle("puzzle1".to_string(), carol.clone());
+        assert_eq!(contract.author_puzzle_count(bob), 0);
+        assert_eq!(contract.author_puzzle_count(carol.clone()), 1);
+        let edit = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.add_clue("puzzle1".to_string(), 2, "b".repeat(64), None, 1)
+        }));
+        assert!(edit.is_err(), "The previous author can't edit any more");
+
+        testing_env!(get_context(carol).build());
+        let fixed = PuzzleMeta {
+            title: "Renamed by the new author".to_string(),
+            ..test_meta()
+        };
+        contract.update_meta("puzzle1".to_string(), fixed.clone());
+        assert_eq!(contract.get_puzzle_meta("puzzle1".to_string()), Some(fixed));
+    }
+
+    #[test]
+    fn check_practice_mode() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, true, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+        testing_env!(get_context(alice).build());
+        contract.set_practice_mode("puzzle1".to_string(), true);
+
+        testing_env!(get_context(bob.clone()).build());
+        let result = contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert!(guess_result(result));
+        assert!(get_created_receipts().is_empty());
+        assert_eq!(contract.prize_pool, 10_000);
+        assert_eq!(contract.rank_of("puzzle1".to_string(), bob), None);
+        assert!(contract.puzzle_tokens("puzzle1".to_string(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn check_estimate_puzzle_storage() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        let no_clues = contract.estimate_puzzle_storage(test_meta(), 0).0;
+        let ten_clues = contract.estimate_puzzle_storage(test_meta(), 10).0;
+        assert_eq!(ten_clues - no_clues, 10 * CLUE_BYTES as Balance * env::storage_byte_cost());
+
+        let before = env::storage_usage();
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        for clue_number in 0..10 {
+            contract.add_clue("puzzle1".to_string(), clue_number, "a".repeat(64), None, 1);
+        }
+        let used = (env::storage_usage() - before) as Balance * env::storage_byte_cost();
+        assert!(used <= ten_clues && ten_clues - used < ten_clues / 10, "Estimate is off: {} vs {}", ten_clues, used);
+    }
+
+    #[test]
+    fn check_notes() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, None, None);
+        contract.attach_note("Thanks for the puzzle!".to_string());
+        assert_eq!(contract.get_my_note(), Some("Thanks for the puzzle!".to_string()));
+
+        testing_env!(get_context(alice).build());
+        assert_eq!(contract.get_note(bob.clone()), Some("Thanks for the puzzle!".to_string()));
+
+        testing_env!(get_context(carol).build());
+        assert_eq!(contract.get_my_note(), None);
+        let read = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.get_note(bob.clone())));
+        assert!(read.is_err(), "Only the owner can read other accounts' notes");
+        let attach = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.attach_note("Hi".to_string())));
+        assert!(attach.is_err(), "Only winners can attach a note");
+    }
+
+    #[test]
+    fn check_sudden_death() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_sudden_death("puzzle1".to_string(), true);
+
+        testing_env!(get_context(bob.clone()).build());
+        assert!(!contract.is_eliminated(bob.clone(), "puzzle1".to_string()));
+        let result = contract.guess_solution("wrong answer here".to_string(), None, Some("puzzle1".to_string()), None);
+        assert!(!guess_result(result));
+        assert!(contract.is_eliminated(bob.clone(), "puzzle1".to_string()));
+        assert!(contract.playable_puzzles(bob).is_empty());
+
+        let guess = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None)
+        }));
+        assert!(guess.is_err(), "Even the right answer is refused once eliminated");
+        let clue = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.guess_clue("puzzle1".to_string(), 1, "near".to_string(), None, None)
+        }));
+        assert!(clue.is_err());
+    }
+
+    #[test]
+    fn check_burn_fees() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_guess_fee(U128(100));
+        contract.set_burn_fees(true);
+
+        let mut context = get_context(AccountId::new_unchecked("bob.testnet".to_string()));
+        testing_env!(context.attached_deposit(100).build());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        assert_eq!(contract.balance_breakdown().collected_fees.0, 0);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id.as_str(), BURN_ACCOUNT);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 100 }]);
+    }
+
+    #[test]
+    fn check_avg_guess_gas() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        assert_eq!(contract.avg_guess_gas(), 0);
+        for player in ["bob.testnet", "carol.testnet", "dave.testnet"].iter() {
+            testing_env!(get_context(AccountId::new_unchecked(player.to_string())).build());
+            contract.guess_solution("wrong answer here".to_string(), None, None, None);
+        }
+        assert_eq!(contract.gas_samples, 3);
+        assert!(contract.avg_guess_gas() > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Winner limit overflow")]
+    fn check_increase_max_winners_overflow() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_max_winners("puzzle1".to_string(), Some(2));
+        contract.increase_max_winners("puzzle1".to_string(), u32::MAX);
+    }
+
+    #[test]
+    fn check_increase_max_winners() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let carol = AccountId::new_unchecked("carol.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_max_winners("puzzle1".to_string(), Some(1));
+        testing_env!(context.attached_deposit(10_000).build());
+        contract.fund_prize_pool();
+
+        testing_env!(get_context(bob).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        assert_eq!(get_created_receipts().len(), 1);
+
+        testing_env!(context.attached_deposit(6_000).build());
+        contract.fund_prize_pool();
+        testing_env!(get_context(alice).build());
+        contract.increase_max_winners("puzzle1".to_string(), 1);
+
+        testing_env!(get_context(carol.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, carol);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 6_000 }]);
+    }
+
+    #[test]
+    fn check_state_fingerprint() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        let before = contract.state_fingerprint();
+        assert_eq!(before.len(), 64);
+        assert_eq!(contract.state_fingerprint(), before);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_ne!(contract.state_fingerprint(), before);
+    }
+
+    #[test]
+    fn check_total_committed_prizes() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.attached_deposit(3_000).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        testing_env!(context.attached_deposit(4_000).build());
+        contract.add_funded_puzzle("puzzle2".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.total_committed_prizes().0, 7_000);
+        contract.assert_prizes_covered();
+
+        testing_env!(context.account_balance(6_000).build());
+        let uncovered = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.assert_prizes_covered()));
+        assert!(uncovered.is_err());
+    }
+
+    #[test]
+    fn check_balance_breakdown() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new("69c2feb084439956193f4c21936025f14a5a5a78979d67ae34762e18a7206a0f".to_string());
+        contract.set_guess_fee(U128(1_000));
+
+        testing_env!(context.attached_deposit(5_000_000).build());
+        contract.fund_prize_pool();
+        testing_env!(context.attached_deposit(1_000).build());
+        contract.guess_solution("wrong answer here".to_string(), None, None, None);
+
+        let breakdown = contract.balance_breakdown();
+        assert_eq!(breakdown.prize_pool.0, 5_000_000);
+        assert_eq!(breakdown.collected_fees.0, 1_000);
+        assert!(breakdown.storage_stake.0 > 0);
+        let parts = breakdown.prize_pool.0
+            + breakdown.collected_fees.0
+            + breakdown.letter_escrow.0
+            + breakdown.storage_stake.0
+            + breakdown.free.0;
+        assert_eq!(parts, breakdown.total.0, "The parts should add up to the total balance.");
+    }
+
+    #[test]
+    fn check_add_funded_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.attached_deposit(3_000).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_funded_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 3_000);
+        assert_eq!(contract.balance_breakdown().prize_pool.0, 3_000);
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 3_000 }]);
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 0);
+    }
+
+    #[test]
+    fn check_linked_puzzle_payout() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(3_000).build());
+        contract.add_funded_puzzle("finale".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        testing_env!(context.attached_deposit(2_000).build());
+        contract.add_funded_puzzle("week1".to_string(), "a".repeat(64), test_meta());
+        contract.set_linked_puzzle("finale".to_string(), Some("week1".to_string()));
+
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("finale".to_string()), None);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, bob);
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 5_000 }]);
+        assert_eq!(contract.puzzle_prize_pool("finale".to_string()).0, 0);
+        assert_eq!(contract.puzzle_prize_pool("week1".to_string()).0, 0);
+    }
+
+    #[test]
+    fn check_token_reward() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        let usdc = AccountId::new_unchecked("usdc.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_token("puzzle1".to_string(), Some(usdc.clone()));
+        testing_env!(get_context(usdc.clone()).build());
+        let refund = contract.ft_on_transfer(bob.clone(), U128(500), "puzzle1".to_string());
+        assert_eq!(refund.0, 0);
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 500);
+
+        testing_env!(get_context(bob).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, usdc);
+        assert!(format!("{:?}", receipts[0].actions[0]).contains("ft_transfer"));
+        assert_eq!(contract.puzzle_prize_pool("puzzle1".to_string()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough gas attached to pay a token reward, attach at least 30 TGas")]
+    fn check_token_reward_gas() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_token("puzzle1".to_string(), Some(AccountId::new_unchecked("usdc.testnet".to_string())));
+
+        let mut context = get_context(AccountId::new_unchecked("bob.testnet".to_string()));
+        testing_env!(context.prepaid_gas(Gas(15 * TGAS)).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("puzzle1".to_string()), None);
+    }
+
+    #[test]
+    fn check_puzzle_view() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        testing_env!(context.attached_deposit(5_000_000).build());
+        contract.fund_prize_pool();
+
+        let view = contract.puzzle_view("puzzle1".to_string()).unwrap();
+        assert_eq!(view.meta, test_meta());
+        assert_eq!(view.status, PuzzleStatus::Active);
+        assert_eq!(view.potential_reward.0, 5_000_000);
+        assert_eq!(view.attempts_remaining, None);
+        assert!(contract.puzzle_view("puzzle2".to_string()).is_none());
+    }
+
+    #[test]
+    fn check_clone_puzzle() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let bob = AccountId::new_unchecked("bob.testnet".to_string());
+        testing_env!(get_context(alice.clone()).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("week1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_clue("week1".to_string(), 1, "a".repeat(64), None, 1);
+        contract.add_clue("week1".to_string(), 2, "b".repeat(64), None, 1);
+        contract.set_max_attempts("week1".to_string(), Some(50));
+        contract.set_max_winners("week1".to_string(), Some(3));
+        testing_env!(get_context(bob.clone()).build());
+        contract.guess_solution("near nomicon ref finance".to_string(), None, Some("week1".to_string()), None);
+
+        testing_env!(get_context(alice).build());
+        contract.clone_puzzle("week1".to_string(), "week2".to_string(), U64(1_000));
+        assert_eq!(contract.puzzle_status("week2".to_string()), PuzzleStatus::Active);
+        assert_eq!(contract.solver_count("week2".to_string()), 0);
+        assert_eq!(contract.rank_of("week2".to_string(), bob.clone()), None);
+        assert_eq!(contract.global_attempts_remaining("week2".to_string()), Some(50));
+        assert_eq!(contract.unsolved_clues(bob, "week2".to_string()), vec![1, 2]);
+        assert_eq!(
+            contract.diff_puzzles("week1".to_string(), "week2".to_string()),
+            vec!["deadline: None vs Some(1000)".to_string()]
+        );
+    }
+
+    #[test]
+    fn check_diff_puzzles() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        testing_env!(get_context(alice).build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.add_puzzle("template".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.add_puzzle("clone".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert!(contract.diff_puzzles("template".to_string(), "clone".to_string()).is_empty());
+
+        contract.set_puzzle_deadline("clone".to_string(), Some(U64(1_000)));
+        contract.set_max_attempts("clone".to_string(), Some(5));
+        contract.set_max_winners("clone".to_string(), Some(3));
+        assert_eq!(
+            contract.diff_puzzles("template".to_string(), "clone".to_string()),
+            vec![
+                "deadline: None vs Some(1000)".to_string(),
+                "max_attempts: None vs Some(5)".to_string(),
+                "max_winners: None vs Some(3)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_reward_info() {
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let usdc = AccountId::new_unchecked("usdc.testnet".to_string());
+        let mut context = get_context(alice.clone());
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        testing_env!(context.attached_deposit(5_000).build());
+        contract.add_funded_puzzle("native".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        testing_env!(context.attached_deposit(0).build());
+        contract.add_puzzle("stable".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        contract.set_reward_token("stable".to_string(), Some(usdc.clone()));
+        testing_env!(get_context(usdc.clone()).build());
+        contract.ft_on_transfer(alice, U128(10_000_000), "stable".to_string());
+
+        assert_eq!(contract.reward_info("native".to_string()), RewardInfo { token: None, amount: U128(5_000) });
+        assert_eq!(
+            contract.reward_info("stable".to_string()),
+            RewardInfo { token: Some(usdc), amount: U128(10_000_000) }
+        );
+    }
+
+    #[test]
+    fn check_potential_reward_human() {
+        assert_eq!(format_near(1_000_000_000_000_000_000_000_000), "1");
+        assert_eq!(format_near(0), "0");
+        assert_eq!(format_near(1_500_000_000_000_000_000_000_000), "1.5");
+        assert_eq!(format_near(1), "0.000000000000000000000001");
+        assert_eq!(format_near(u128::MAX), "340282366920938.463463374607431768211455");
+
+        let alice = AccountId::new_unchecked("alice.testnet".to_string());
+        let mut context = get_context(alice);
+        testing_env!(context.build());
+        let mut contract = Contract::new(SOLUTION_HASH.to_string());
+        contract.set_features(true, false, false);
+        contract.add_puzzle("puzzle1".to_string(), SOLUTION_HASH.to_string(), test_meta());
+        assert_eq!(contract.potential_reward_human("puzzle1".to_string()), "0");
+        testing_env!(context.attached_deposit(2_250_000_000_000_000_000_000_000).build());
+        contract.fund_prize_pool();
+        assert_eq!(contract.potential_reward_human("puzzle1".to_string()), "2.25");
+    }
 }
 
 